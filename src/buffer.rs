@@ -1,35 +1,266 @@
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE};
 use ropey::Rope;
-use std::fs;
+use std::{cell::RefCell, fs, path::Path};
+
+use crate::language::LanguageDef;
+use crate::syntax::{LineState, SyntaxCache};
+use crate::undo::{Edit, EditKind, History};
+use crate::{encoding, file_io};
+
+/// Estilo de fin de línea del archivo
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n` (Unix)
+    Lf,
+
+    /// `\r\n` (Windows)
+    Crlf,
+}
+
+impl LineEnding {
+    /// Detecta el estilo predominante en un texto
+    ///
+    /// Si hay más líneas terminadas en `\r\n` que en `\n` solo, se considera CRLF.
+    pub fn detect(content: &str) -> Self {
+        let crlf = content.matches("\r\n").count();
+        let lf = content.matches('\n').count() - crlf;
+
+        if crlf > lf {
+            LineEnding::Crlf
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    /// Indica si el texto tiene líneas terminadas tanto en `\r\n` como en `\n`
+    pub fn is_mixed(content: &str) -> bool {
+        let crlf = content.matches("\r\n").count();
+        crlf > 0 && content.matches('\n').count() > crlf
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+
+    /// Nombre corto para la barra de estado
+    pub fn label(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::Crlf => "CRLF",
+        }
+    }
+}
+
+/// Lo que determina el contenido del archivo en disco al momento de guardar
+#[derive(Clone, Copy, PartialEq)]
+struct SavedState {
+    history: u64,
+    line_ending: LineEnding,
+    mixed_line_endings: bool,
+    encoding: &'static Encoding,
+    has_bom: bool,
+}
 
 /// Representa el buffer de texto del documento
 ///
 /// Un buffer contiene las líneas del documento y proporciona
-/// operaciones para manipular el texto
+/// operaciones para manipular el texto. Internamente las líneas siempre
+/// terminan en `\n`; el estilo original se restaura al guardar.
 pub struct TextBuffer {
     rope: Rope,
+    history: History,
+    line_ending: LineEnding,
+
+    /// El archivo tenía finales de línea mixtos: al guardar se unifican
+    mixed_line_endings: bool,
+    final_newline: bool,
+    encoding: &'static Encoding,
+    has_bom: bool,
+
+    /// Contador que aumenta con cada cambio del texto
+    revision: u64,
+
+    /// Estado al abrir o guardar por última vez
+    saved: SavedState,
+
+    /// Estado del resaltado al inicio de cada línea; se completa al dibujar
+    syntax: RefCell<SyntaxCache>,
 }
 
 impl TextBuffer {
     pub fn new() -> Self {
-        TextBuffer { rope: Rope::new() }
+        TextBuffer {
+            rope: Rope::new(),
+            history: History::new(),
+            line_ending: LineEnding::Lf,
+            mixed_line_endings: false,
+            final_newline: true,
+            encoding: UTF_8,
+            has_bom: false,
+            revision: 0,
+            saved: SavedState {
+                history: 0,
+                line_ending: LineEnding::Lf,
+                mixed_line_endings: false,
+                encoding: UTF_8,
+                has_bom: false,
+            },
+            syntax: RefCell::default(),
+        }
     }
 
-    /// Crea un buffer desde un archivo
+    /// Crea un buffer desde un archivo, detectando su codificación
     pub fn from_file(path: &str) -> std::io::Result<Self> {
-        let mut content = fs::read_to_string(path)?;
+        let bytes = fs::read(path)?;
+        Ok(Self::from_bytes(&bytes))
+    }
+
+    /// Crea un buffer desde bytes leídos de otra fuente, detectando su codificación
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::from_decoded(encoding::decode(bytes))
+    }
+
+    /// Crea un buffer desde un archivo forzando una codificación
+    pub fn from_file_with_encoding(
+        path: &str,
+        encoding: &'static Encoding,
+    ) -> std::io::Result<Self> {
+        let bytes = fs::read(path)?;
+        Ok(Self::from_decoded(encoding::decode_with(&bytes, encoding)))
+    }
 
-        if content.is_empty() || !content.ends_with('\n') {
+    fn from_decoded(decoded: encoding::Decoded) -> Self {
+        let mut buffer = Self::from_content(&decoded.text);
+        buffer.encoding = decoded.encoding;
+        buffer.has_bom = decoded.has_bom;
+        buffer.saved = buffer.current_state();
+        buffer
+    }
+
+    /// Crea un buffer desde el contenido de un archivo, detectando el fin
+    /// de línea y si el archivo terminaba con salto de línea
+    fn from_content(content: &str) -> Self {
+        let line_ending = LineEnding::detect(content);
+        let mixed_line_endings = LineEnding::is_mixed(content);
+        let mut content = content.replace("\r\n", "\n");
+        let final_newline = content.ends_with('\n');
+
+        if !final_newline {
             content.push('\n');
         }
 
-        Ok(Self {
+        Self {
             rope: Rope::from_str(&content),
-        })
+            history: History::new(),
+            line_ending,
+            mixed_line_endings,
+            final_newline,
+            encoding: UTF_8,
+            has_bom: false,
+            revision: 0,
+            saved: SavedState {
+                history: 0,
+                line_ending,
+                mixed_line_endings,
+                encoding: UTF_8,
+                has_bom: false,
+            },
+            syntax: RefCell::default(),
+        }
+    }
+
+    /// Guarda el buffer en un archivo usando su codificación
+    pub fn save_to_file(&self, path: &str, backup: bool) -> std::io::Result<()> {
+        let bytes = encoding::encode(&self.file_content(), self.encoding, self.has_bom)?;
+        file_io::write_atomic(Path::new(path), &bytes, backup)
+    }
+
+    /// Genera el contenido a escribir en disco respetando el fin de línea
+    /// y el salto de línea final del archivo original
+    fn file_content(&self) -> String {
+        let mut content = self.rope.to_string();
+
+        if self.final_newline {
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+        } else if content.ends_with('\n') {
+            content.pop();
+        }
+
+        match self.line_ending {
+            LineEnding::Lf => content,
+            LineEnding::Crlf => content.replace('\n', LineEnding::Crlf.as_str()),
+        }
+    }
+
+    /// Registra el estado actual como el guardado en disco
+    pub fn mark_saved(&mut self) {
+        self.history.seal();
+        self.saved = self.current_state();
+    }
+
+    /// Indica si el buffer coincide con lo último abierto o guardado,
+    /// por ejemplo después de deshacer todos los cambios
+    pub fn is_saved(&self) -> bool {
+        self.current_state() == self.saved
+    }
+
+    fn current_state(&self) -> SavedState {
+        SavedState {
+            history: self.history.state_id(),
+            line_ending: self.line_ending,
+            mixed_line_endings: self.mixed_line_endings,
+            encoding: self.encoding,
+            has_bom: self.has_bom,
+        }
     }
 
-    /// Guarda el buffer en un archivo
-    pub fn save_to_file(&self, path: &str) -> std::io::Result<()> {
-        fs::write(path, self.rope.to_string())
+    /// Obtiene el estilo de fin de línea usado al guardar
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Cambia el estilo de fin de línea usado al guardar
+    pub fn set_line_ending(&mut self, line_ending: LineEnding) {
+        self.line_ending = line_ending;
+        self.mixed_line_endings = false;
+    }
+
+    /// Indica si el archivo se abrió con finales de línea mixtos y todavía no
+    /// se eligió con cuál guardarlo
+    pub fn has_mixed_line_endings(&self) -> bool {
+        self.mixed_line_endings
+    }
+
+    /// Fin de línea para la barra de estado (`LF`, o `LF*` si era mixto)
+    pub fn line_ending_label(&self) -> String {
+        let mixed = if self.mixed_line_endings { "*" } else { "" };
+        format!("{}{}", self.line_ending.label(), mixed)
+    }
+
+    /// Obtiene la codificación usada al guardar
+    pub fn encoding(&self) -> &'static Encoding {
+        self.encoding
+    }
+
+    /// Cambia la codificación usada al guardar
+    ///
+    /// El BOM solo se conserva si la nueva codificación es Unicode. UTF-16
+    /// siempre se guarda con BOM: sin él, al reabrir el archivo no se detecta
+    /// la codificación y se lee como UTF-8 o Windows-1252.
+    pub fn set_encoding(&mut self, encoding: &'static Encoding) {
+        let is_utf16 = encoding == UTF_16LE || encoding == UTF_16BE;
+        self.has_bom = is_utf16 || (self.has_bom && encoding == UTF_8);
+        self.encoding = encoding;
+    }
+
+    /// Nombre de la codificación para la barra de estado
+    pub fn encoding_label(&self) -> String {
+        encoding::label(self.encoding, self.has_bom)
     }
 
     /// Obtiene la linea perteneciente al indice indicado (sin \n final)
@@ -51,6 +282,30 @@ impl TextBuffer {
         }
     }
 
+    /// Obtiene el texto completo del buffer, con `\n` como fin de línea
+    pub fn text(&self) -> String {
+        self.rope.to_string()
+    }
+
+    /// Reemplaza todo el texto en un único paso de deshacer
+    ///
+    /// # Argumentos
+    /// * `text` - Texto nuevo, con `\n` como fin de línea
+    /// * `cursor` - Posición (línea, columna) del cursor antes del cambio
+    pub fn replace_text(&mut self, text: &str, cursor: (usize, usize)) {
+        let old_text = self.rope.to_string();
+
+        self.begin_undo_group(cursor);
+        self.apply_edit(Edit::delete(0, &old_text), cursor);
+        self.apply_edit(Edit::insert(0, text), (0, 0));
+        self.end_undo_group(cursor);
+    }
+
+    /// Número de revisión del texto; cambia con cada edición, deshacer o rehacer
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
     pub fn iter_lines(&self) -> impl Iterator<Item = String> + '_ {
         (0..self.line_count()).map(|i| self.line(i))
     }
@@ -97,8 +352,14 @@ impl TextBuffer {
         // Calcular la posición absoluta en el rope
         let char_idx = line_start + safe_col;
 
-        // Insertar el carácter
+        // Insertar el carácter y registrarlo como escritura continua
         self.rope.insert_char(char_idx, ch);
+        self.mark_changed(char_idx);
+        self.history.record_typing(
+            Edit::insert(char_idx, ch.encode_utf8(&mut [0; 4])),
+            (line_idx, safe_col),
+            self.char_to_position(char_idx + 1),
+        );
     }
 
     /// Inserta una cadena en una posición específica
@@ -113,7 +374,7 @@ impl TextBuffer {
         let safe_col = col.min(line_len);
         let char_idx = line_start + safe_col;
 
-        self.rope.insert(char_idx, text);
+        self.apply_edit(Edit::insert(char_idx, text), (line_idx, safe_col));
     }
     /// Elimina el carácter antes de la posición especificada
     ///
@@ -136,8 +397,9 @@ impl TextBuffer {
 
         let line_start = self.rope.line_to_char(line_idx);
         let char_idx = line_start + col - 1;
+        let removed = self.rope.slice(char_idx..char_idx + 1).to_string();
 
-        self.rope.remove(char_idx..char_idx + 1);
+        self.apply_edit(Edit::delete(char_idx, &removed), (line_idx, col));
 
         true
     }
@@ -160,7 +422,7 @@ impl TextBuffer {
         let newline_pos = prev_line_start + prev_len;
 
         // Eliminar el \n
-        self.rope.remove(newline_pos..newline_pos + 1);
+        self.apply_edit(Edit::delete(newline_pos, "\n"), (line_idx, 0));
 
         prev_len
     }
@@ -178,11 +440,150 @@ impl TextBuffer {
         let safe_col = col.min(line_len);
 
         let char_idx = line_start + safe_col;
-        self.rope.insert_char(char_idx, '\n');
+        self.apply_edit(Edit::insert(char_idx, "\n"), (line_idx, safe_col));
 
         (line_idx + 1, 0)
     }
 
+    /// Obtiene el texto comprendido entre dos posiciones
+    ///
+    /// # Argumentos
+    /// * `start` - Posición (línea, columna) inicial, inclusiva
+    /// * `end` - Posición (línea, columna) final, exclusiva
+    pub fn text_range(&self, start: (usize, usize), end: (usize, usize)) -> String {
+        let start_idx = self.position_to_char(start);
+        let end_idx = self.position_to_char(end).max(start_idx);
+
+        self.rope.slice(start_idx..end_idx).to_string()
+    }
+
+    /// Elimina el texto comprendido entre dos posiciones
+    ///
+    /// # Argumentos
+    /// * `start` - Posición (línea, columna) inicial, inclusiva
+    /// * `end` - Posición (línea, columna) final, exclusiva
+    ///
+    /// # Retorna
+    /// El texto eliminado
+    pub fn delete_range(&mut self, start: (usize, usize), end: (usize, usize)) -> String {
+        let removed = self.text_range(start, end);
+
+        if !removed.is_empty() {
+            let start_idx = self.position_to_char(start);
+            self.apply_edit(Edit::delete(start_idx, &removed), end);
+        }
+
+        removed
+    }
+
+    /// Deshace el último paso del historial
+    ///
+    /// # Retorna
+    /// La posición (línea, columna) del cursor antes del cambio deshecho,
+    /// o `None` si no había nada que deshacer
+    pub fn undo(&mut self) -> Option<(usize, usize)> {
+        let step = self.history.undo()?;
+
+        for edit in step.edits.iter().rev() {
+            match edit.kind {
+                EditKind::Insert => self.remove_text(edit.char_idx, &edit.text),
+                EditKind::Delete => self.rope.insert(edit.char_idx, &edit.text),
+            }
+            self.mark_changed(edit.char_idx);
+        }
+
+        Some(step.cursor_before)
+    }
+
+    /// Rehace el último paso deshecho
+    ///
+    /// # Retorna
+    /// La posición (línea, columna) del cursor después del cambio rehecho,
+    /// o `None` si no había nada que rehacer
+    pub fn redo(&mut self) -> Option<(usize, usize)> {
+        let step = self.history.redo()?;
+
+        for edit in &step.edits {
+            match edit.kind {
+                EditKind::Insert => self.rope.insert(edit.char_idx, &edit.text),
+                EditKind::Delete => self.remove_text(edit.char_idx, &edit.text),
+            }
+            self.mark_changed(edit.char_idx);
+        }
+
+        Some(step.cursor_after)
+    }
+
+    /// Inicia un grupo de ediciones que se deshacen en un solo paso
+    ///
+    /// # Argumentos
+    /// * `cursor` - Posición (línea, columna) del cursor antes del cambio
+    pub fn begin_undo_group(&mut self, cursor: (usize, usize)) {
+        self.history.begin_group(cursor);
+    }
+
+    /// Cierra el grupo de ediciones abierto con `begin_undo_group`
+    ///
+    /// # Argumentos
+    /// * `cursor` - Posición (línea, columna) del cursor después del cambio
+    pub fn end_undo_group(&mut self, cursor: (usize, usize)) {
+        self.history.end_group(cursor);
+    }
+
+    /// Aplica una edición sobre el rope y la registra en el historial
+    fn apply_edit(&mut self, edit: Edit, cursor_before: (usize, usize)) {
+        let cursor_after = match edit.kind {
+            EditKind::Insert => {
+                self.rope.insert(edit.char_idx, &edit.text);
+                self.char_to_position(edit.char_idx + edit.text.chars().count())
+            }
+            EditKind::Delete => {
+                self.remove_text(edit.char_idx, &edit.text);
+                self.char_to_position(edit.char_idx)
+            }
+        };
+
+        self.mark_changed(edit.char_idx);
+        self.history.record(edit, cursor_before, cursor_after);
+    }
+
+    /// Registra un cambio del texto que empieza en `char_idx`
+    ///
+    /// Aumenta la revisión y descarta los estados de resaltado desde la
+    /// línea modificada hacia abajo.
+    fn mark_changed(&mut self, char_idx: usize) {
+        self.revision += 1;
+        let line = self.rope.char_to_line(char_idx.min(self.rope.len_chars()));
+        self.syntax.get_mut().invalidate_from(line);
+    }
+
+    /// Estado del resaltado de sintaxis al inicio de una línea
+    ///
+    /// Los estados se calculan desde la primera línea la primera vez y
+    /// quedan guardados hasta la próxima edición.
+    pub fn syntax_state(&self, line_idx: usize, language: &LanguageDef) -> LineState {
+        self.syntax
+            .borrow_mut()
+            .state_at(line_idx, language, |idx| self.line(idx))
+    }
+
+    fn remove_text(&mut self, char_idx: usize, text: &str) {
+        let end = char_idx + text.chars().count();
+        self.rope.remove(char_idx..end);
+    }
+
+    /// Convierte una posición (línea, columna) en un índice de carácter del rope
+    fn position_to_char(&self, (line_idx, col): (usize, usize)) -> usize {
+        let line_idx = line_idx.min(self.line_count() - 1);
+        self.rope.line_to_char(line_idx) + self.clamp_column(line_idx, col)
+    }
+
+    /// Convierte un índice de carácter del rope en (línea, columna)
+    fn char_to_position(&self, char_idx: usize) -> (usize, usize) {
+        let line = self.rope.char_to_line(char_idx);
+        (line, char_idx - self.rope.line_to_char(line))
+    }
+
     /// Verifica si un índice de línea es válido
     pub fn is_valid_line(&self, line_idx: usize) -> bool {
         line_idx < self.line_count()
@@ -265,4 +666,207 @@ mod tests {
         assert_eq!(buffer.line(0), "hiby");
         assert_eq!(prev_len, 2);
     }
+
+    #[test]
+    fn test_text_and_delete_range_across_lines() {
+        let mut buffer = TextBuffer::new();
+        buffer.insert_str(0, 0, "hola\nmundo\nfin");
+
+        assert_eq!(buffer.text_range((0, 2), (1, 3)), "la\nmun");
+
+        let removed = buffer.delete_range((0, 2), (1, 3));
+
+        assert_eq!(removed, "la\nmun");
+        assert_eq!(buffer.line_count(), 2);
+        assert_eq!(buffer.line(0), "hodo");
+
+        assert_eq!(buffer.undo(), Some((1, 3)));
+        assert_eq!(buffer.line(1), "mundo");
+    }
+
+    #[test]
+    fn test_crlf_file_is_normalized_and_restored() {
+        let buffer = TextBuffer::from_content("uno\r\ndos\r\n");
+
+        assert_eq!(buffer.line_ending(), LineEnding::Crlf);
+        assert_eq!(buffer.line(0), "uno");
+        assert_eq!(buffer.line_length(0), 3);
+        assert_eq!(buffer.file_content(), "uno\r\ndos\r\n");
+    }
+
+    #[test]
+    fn test_mixed_line_endings_are_flagged_until_chosen() {
+        let mut buffer = TextBuffer::from_content("uno\r\ndos\ntres\r\n");
+
+        assert!(buffer.has_mixed_line_endings());
+        assert_eq!(buffer.line_ending_label(), "CRLF*");
+
+        buffer.set_line_ending(LineEnding::Lf);
+        assert!(!buffer.has_mixed_line_endings());
+        assert_eq!(buffer.line_ending_label(), "LF");
+
+        assert!(!TextBuffer::from_content("uno\r\ndos\r\n").has_mixed_line_endings());
+        assert!(!TextBuffer::from_content("uno\ndos").has_mixed_line_endings());
+    }
+
+    #[test]
+    fn test_missing_final_newline_is_preserved() {
+        let buffer = TextBuffer::from_content("uno\ndos");
+
+        assert_eq!(buffer.line_ending(), LineEnding::Lf);
+        assert_eq!(buffer.line_count(), 3);
+        assert_eq!(buffer.file_content(), "uno\ndos");
+
+        let empty = TextBuffer::from_content("");
+        assert_eq!(empty.file_content(), "");
+    }
+
+    #[test]
+    fn test_final_newline_added_after_editing_last_line() {
+        let mut buffer = TextBuffer::from_content("uno\n");
+        buffer.insert_char(1, 0, 'x');
+
+        assert_eq!(buffer.file_content(), "uno\nx\n");
+    }
+
+    #[test]
+    fn test_save_and_reload_legacy_encoding() {
+        let path =
+            std::env::temp_dir().join(format!("hyperion_buffer_latin1_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(path, b"a\xF1o\n").unwrap();
+
+        let mut buffer = TextBuffer::from_file(path).unwrap();
+        assert_eq!(buffer.line(0), "año");
+        assert_eq!(buffer.encoding(), encoding_rs::WINDOWS_1252);
+
+        buffer.insert_char(0, 3, 's');
+        buffer.save_to_file(path, false).unwrap();
+        assert_eq!(fs::read(path).unwrap(), b"a\xF1os\n");
+
+        let reopened = TextBuffer::from_file_with_encoding(path, UTF_8).unwrap();
+        assert_eq!(reopened.line(0), "a\u{FFFD}os");
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_convert_to_utf16_reopens_as_utf16() {
+        let path =
+            std::env::temp_dir().join(format!("hyperion_buffer_utf16_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(path, "año\n").unwrap();
+
+        let mut buffer = TextBuffer::from_file(path).unwrap();
+        buffer.set_encoding(UTF_16LE);
+        buffer.save_to_file(path, false).unwrap();
+        assert_eq!(&fs::read(path).unwrap()[..2], b"\xFF\xFE");
+
+        let reopened = TextBuffer::from_file(path).unwrap();
+        assert_eq!(reopened.encoding(), UTF_16LE);
+        assert_eq!(reopened.line(0), "año");
+        assert_eq!(reopened.encoding_label(), "UTF-16LE BOM");
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_convert_line_ending() {
+        let mut buffer = TextBuffer::from_content("a\nb\n");
+        buffer.set_line_ending(LineEnding::Crlf);
+
+        assert_eq!(buffer.file_content(), "a\r\nb\r\n");
+    }
+
+    #[test]
+    fn test_replace_text_is_one_undo_step() {
+        let mut buffer = TextBuffer::from_content("uno\ndos\n");
+        let revision = buffer.revision();
+
+        buffer.replace_text("tres\n", (0, 0));
+        assert_eq!(buffer.text(), "tres\n");
+        assert!(buffer.revision() > revision);
+
+        buffer.undo();
+        assert_eq!(buffer.text(), "uno\ndos\n");
+    }
+
+    #[test]
+    fn test_undo_typing_as_single_step() {
+        let mut buffer = TextBuffer::new();
+        buffer.insert_char(0, 0, 'h');
+        buffer.insert_char(0, 1, 'o');
+        buffer.insert_char(0, 2, 'l');
+        buffer.insert_char(0, 3, 'a');
+
+        let cursor = buffer.undo();
+
+        assert_eq!(buffer.line(0), "");
+        assert_eq!(cursor, Some((0, 0)));
+        assert_eq!(buffer.undo(), None);
+    }
+
+    #[test]
+    fn test_undo_and_redo_split_line() {
+        let mut buffer = TextBuffer::new();
+        buffer.insert_str(0, 0, "hello");
+        buffer.split_line(0, 2);
+
+        assert_eq!(buffer.undo(), Some((0, 2)));
+        assert_eq!(buffer.line_count(), 1);
+        assert_eq!(buffer.line(0), "hello");
+
+        assert_eq!(buffer.redo(), Some((1, 0)));
+        assert_eq!(buffer.line(0), "he");
+        assert_eq!(buffer.line(1), "llo");
+    }
+
+    #[test]
+    fn test_undo_delete_and_join() {
+        let mut buffer = TextBuffer::new();
+        buffer.insert_str(0, 0, "hi\nby");
+        buffer.delete_char(1, 2);
+        buffer.join_with_previous(1);
+        assert_eq!(buffer.line(0), "hib");
+
+        assert_eq!(buffer.undo(), Some((1, 0)));
+        assert_eq!(buffer.line(1), "b");
+
+        assert_eq!(buffer.undo(), Some((1, 2)));
+        assert_eq!(buffer.line(1), "by");
+    }
+
+    #[test]
+    fn test_undo_group() {
+        let mut buffer = TextBuffer::new();
+        buffer.insert_str(0, 0, "abc");
+
+        buffer.begin_undo_group((0, 3));
+        buffer.delete_char(0, 3);
+        buffer.delete_char(0, 2);
+        buffer.end_undo_group((0, 1));
+
+        assert_eq!(buffer.line(0), "a");
+        assert_eq!(buffer.undo(), Some((0, 3)));
+        assert_eq!(buffer.line(0), "abc");
+        assert_eq!(buffer.redo(), Some((0, 1)));
+        assert_eq!(buffer.line(0), "a");
+    }
+
+    #[test]
+    fn syntax_state_follows_edits_and_undo() {
+        let mut buffer = TextBuffer::from_content("let a;\nlet b;\nlet c;\n");
+        let languages = crate::language::Languages::builtin();
+        let rust = languages.detect(Some("main.rs")).unwrap();
+        assert_eq!(buffer.syntax_state(2, rust), LineState::Normal);
+
+        buffer.insert_str(0, 0, "/* ");
+        assert_eq!(
+            buffer.syntax_state(2, rust),
+            LineState::BlockComment { depth: 1 }
+        );
+
+        buffer.undo();
+        assert_eq!(buffer.syntax_state(2, rust), LineState::Normal);
+    }
 }
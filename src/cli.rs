@@ -1,6 +1,7 @@
 //! Módulo de parsing de argumentos de línea de comandos
 
 use clap::Parser;
+use std::path::Path;
 
 /// Editor de texto simple en terminal
 #[derive(Parser, Debug)]
@@ -9,13 +10,195 @@ use clap::Parser;
 #[command(version = "1.0.0")]
 #[command(about = "Editor de texto minimalista para terminal", long_about = None)]
 pub struct Args {
-    /// Archivo a abrir o crear
+    /// Archivos a abrir o crear; aceptan `ruta:linea[:columna]`, y `+linea`
+    /// antes de un archivo indica la línea donde posicionarse. `-` lee la
+    /// entrada estándar
     #[arg(value_name = "FILE")]
-    pub file: Option<String>,
+    pub files: Vec<String>,
+
+    /// Abrir los archivos en modo solo lectura
+    #[arg(short = 'R', long)]
+    pub readonly: bool,
 }
 
 impl Args {
     pub fn parse_args() -> Self {
         Args::parse()
     }
+
+    /// Archivos a abrir, con la posición indicada para cada uno
+    ///
+    /// Si no se indicó ningún archivo y la entrada estándar no es una
+    /// terminal (`git diff | hy`), se lee la entrada estándar.
+    pub fn targets(&self, stdin_is_terminal: bool) -> Vec<FileTarget> {
+        let mut targets = parse_targets(&self.files, |path| Path::new(path).exists());
+
+        if targets.is_empty() && !stdin_is_terminal {
+            targets.push(FileTarget {
+                path: STDIN_ARG.to_string(),
+                line: None,
+                column: None,
+            });
+        }
+
+        targets
+    }
+}
+
+/// Argumento que indica leer la entrada estándar
+pub const STDIN_ARG: &str = "-";
+
+/// Archivo pedido en la línea de comandos
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileTarget {
+    pub path: String,
+
+    /// Línea y columna (base 1) donde posicionar el cursor
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl FileTarget {
+    /// Indica si el objetivo es la entrada estándar (`-`)
+    pub fn is_stdin(&self) -> bool {
+        self.path == STDIN_ARG
+    }
+}
+
+/// Interpreta los argumentos de archivo
+///
+/// # Argumentos
+/// * `args` - Argumentos tal como se recibieron
+/// * `exists` - Indica si una ruta existe; si `a.txt:3` existe literalmente,
+///   no se interpreta como `a.txt` en la línea 3
+fn parse_targets(args: &[String], exists: impl Fn(&str) -> bool) -> Vec<FileTarget> {
+    let mut targets = Vec::new();
+    let mut pending_line = None;
+
+    for arg in args {
+        if let Some(line) = arg.strip_prefix('+').and_then(|n| n.parse().ok()) {
+            pending_line = Some(line);
+            continue;
+        }
+
+        let mut target = split_position(arg, &exists);
+        if let Some(line) = pending_line.take() {
+            target.line = Some(line);
+            target.column = None;
+        }
+        targets.push(target);
+    }
+
+    targets
+}
+
+/// Separa `ruta:linea[:columna]` (admite el `:` final de la salida de compiladores)
+fn split_position(arg: &str, exists: &impl Fn(&str) -> bool) -> FileTarget {
+    let plain = FileTarget {
+        path: arg.to_string(),
+        line: None,
+        column: None,
+    };
+
+    if exists(arg) {
+        return plain;
+    }
+
+    let trimmed = arg.strip_suffix(':').unwrap_or(arg);
+    let Some((rest, last)) = trimmed.rsplit_once(':') else {
+        return plain;
+    };
+    let Ok(last) = last.parse::<usize>() else {
+        return plain;
+    };
+
+    if let Some((path, line)) = rest.rsplit_once(':')
+        && let Ok(line) = line.parse::<usize>()
+        && !path.is_empty()
+    {
+        return FileTarget {
+            path: path.to_string(),
+            line: Some(line),
+            column: Some(last),
+        };
+    }
+
+    if rest.is_empty() {
+        return plain;
+    }
+
+    FileTarget {
+        path: rest.to_string(),
+        line: Some(last),
+        column: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Vec<FileTarget> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        parse_targets(&args, |path| path == "raro:7")
+    }
+
+    fn target(path: &str, line: Option<usize>, column: Option<usize>) -> FileTarget {
+        FileTarget {
+            path: path.to_string(),
+            line,
+            column,
+        }
+    }
+
+    #[test]
+    fn parse_plain_files() {
+        assert_eq!(
+            parse(&["a.rs", "b.rs"]),
+            vec![target("a.rs", None, None), target("b.rs", None, None)]
+        );
+    }
+
+    #[test]
+    fn parse_line_and_column_suffixes() {
+        assert_eq!(
+            parse(&["src/editor.rs:120:5", "main.rs:3", "lib.rs:10:2:"]),
+            vec![
+                target("src/editor.rs", Some(120), Some(5)),
+                target("main.rs", Some(3), None),
+                target("lib.rs", Some(10), Some(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_plus_line_applies_to_next_file() {
+        assert_eq!(
+            parse(&["+42", "a.rs", "b.rs"]),
+            vec![target("a.rs", Some(42), None), target("b.rs", None, None)]
+        );
+    }
+
+    #[test]
+    fn piped_stdin_is_read_without_arguments() {
+        let args = Args {
+            files: Vec::new(),
+            readonly: false,
+        };
+
+        assert!(args.targets(true).is_empty());
+        assert!(args.targets(false)[0].is_stdin());
+
+        let args = Args {
+            files: vec!["+3".to_string(), "-".to_string()],
+            readonly: false,
+        };
+        assert_eq!(args.targets(true), vec![target("-", Some(3), None)]);
+    }
+
+    #[test]
+    fn existing_path_with_colon_is_kept() {
+        assert_eq!(parse(&["raro:7"]), vec![target("raro:7", None, None)]);
+        assert_eq!(parse(&["nota:x"]), vec![target("nota:x", None, None)]);
+    }
 }
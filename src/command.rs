@@ -0,0 +1,114 @@
+//! Comandos del editor ingresados en el prompt de comandos (Ctrl+E)
+
+use encoding_rs::Encoding;
+
+use crate::buffer::LineEnding;
+use crate::encoding;
+
+/// Comando reconocido por el editor
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Cambia el fin de línea usado al guardar (`lf` / `crlf`)
+    SetLineEnding(LineEnding),
+
+    /// Cambia la codificación usada al guardar (`codificacion <nombre>`)
+    SetEncoding(&'static Encoding),
+
+    /// Vuelve a leer el archivo con otra codificación (`reabrir <nombre>`)
+    ReopenWithEncoding(&'static Encoding),
+
+    /// Activa o desactiva el modo de solo lectura (`solo-lectura`)
+    ToggleReadOnly,
+
+    /// Busca en todos los archivos del proyecto (`grep [texto]`); sin texto
+    /// se pide en un prompt
+    SearchInFiles(String),
+}
+
+/// Interpreta el texto ingresado en el prompt de comandos
+///
+/// # Retorna
+/// El comando reconocido, o un mensaje de error para la barra de estado
+pub fn parse_command(input: &str) -> Result<Command, String> {
+    let mut parts = input.split_whitespace();
+    let Some(name) = parts.next() else {
+        return Err("Comando vacío".to_string());
+    };
+
+    match name.to_ascii_lowercase().as_str() {
+        "lf" => Ok(Command::SetLineEnding(LineEnding::Lf)),
+        "crlf" => Ok(Command::SetLineEnding(LineEnding::Crlf)),
+        "codificacion" | "codificación" => parse_encoding(parts.next()).map(Command::SetEncoding),
+        "reabrir" => parse_encoding(parts.next()).map(Command::ReopenWithEncoding),
+        "solo-lectura" | "ro" => Ok(Command::ToggleReadOnly),
+        "grep" | "buscar-en-archivos" => {
+            // El texto a buscar se conserva tal cual, con sus espacios
+            let query = input.trim_start()[name.len()..].trim();
+            Ok(Command::SearchInFiles(query.to_string()))
+        }
+        _ => Err(format!("Comando desconocido: '{}'", name)),
+    }
+}
+
+fn parse_encoding(label: Option<&str>) -> Result<&'static Encoding, String> {
+    let Some(label) = label else {
+        return Err("Indique una codificación, ej. utf-8 o latin1".to_string());
+    };
+
+    encoding::lookup(label).ok_or_else(|| format!("Codificación desconocida: '{}'", label))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_line_ending_commands() {
+        assert_eq!(
+            parse_command("crlf"),
+            Ok(Command::SetLineEnding(LineEnding::Crlf))
+        );
+        assert_eq!(
+            parse_command(" LF "),
+            Ok(Command::SetLineEnding(LineEnding::Lf))
+        );
+    }
+
+    #[test]
+    fn parse_encoding_commands() {
+        assert_eq!(
+            parse_command("codificacion latin1"),
+            Ok(Command::SetEncoding(encoding_rs::WINDOWS_1252))
+        );
+        assert_eq!(
+            parse_command("reabrir utf-8"),
+            Ok(Command::ReopenWithEncoding(encoding_rs::UTF_8))
+        );
+        assert!(parse_command("reabrir").is_err());
+        assert!(parse_command("codificacion klingon").is_err());
+    }
+
+    #[test]
+    fn parse_read_only_toggle() {
+        assert_eq!(parse_command("solo-lectura"), Ok(Command::ToggleReadOnly));
+        assert_eq!(parse_command("RO"), Ok(Command::ToggleReadOnly));
+    }
+
+    #[test]
+    fn parse_search_in_files_keeps_the_query() {
+        assert_eq!(
+            parse_command("grep re:fn  \\w+"),
+            Ok(Command::SearchInFiles("re:fn  \\w+".to_string()))
+        );
+        assert_eq!(
+            parse_command("buscar-en-archivos"),
+            Ok(Command::SearchInFiles(String::new()))
+        );
+    }
+
+    #[test]
+    fn parse_unknown_command() {
+        assert!(parse_command("foo").is_err());
+        assert!(parse_command("   ").is_err());
+    }
+}
@@ -0,0 +1,309 @@
+//! Autocompletado de rutas en los prompts de abrir y guardar como
+//!
+//! Con `Tab` se completa el prefijo común de los archivos que coinciden con
+//! lo escrito; si no hay más prefijo común, cada `Tab` siguiente recorre los
+//! candidatos. Los directorios se muestran con una `/` final.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::config::expand_home_path;
+
+/// Archivo o directorio que coincide con lo escrito
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candidate {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+impl Candidate {
+    /// Nombre a mostrar e insertar: los directorios terminan en `/`
+    pub fn display(&self) -> String {
+        if self.is_dir {
+            format!("{}/", self.name)
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+/// Estado del autocompletado mientras el prompt está abierto
+#[derive(Debug, Default)]
+pub struct PathCompletion {
+    /// Candidatos de la última vez que se pulsó Tab
+    candidates: Vec<Candidate>,
+
+    /// Directorio de los candidatos, tal como se escribió
+    base: String,
+
+    /// Candidato insertado al recorrer la lista con Tab
+    selected: Option<usize>,
+
+    /// Texto que dejó el último Tab; si cambió, se vuelve a calcular
+    last_text: String,
+}
+
+impl PathCompletion {
+    pub fn new() -> Self {
+        PathCompletion::default()
+    }
+
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.candidates
+    }
+
+    /// Procesa una pulsación de Tab
+    ///
+    /// # Argumentos
+    /// * `input` - Texto actual del prompt
+    ///
+    /// # Retorna
+    /// El nuevo texto del prompt, o `None` si no hay nada que completar
+    pub fn complete(&mut self, input: &str) -> Option<String> {
+        // Tab repetido sin editar: pasar al siguiente candidato
+        if input == self.last_text
+            && self.candidates.len() > 1
+            && let Some(idx) = self.selected
+        {
+            return Some(self.select((idx + 1) % self.candidates.len()));
+        }
+
+        // Tras completar el prefijo común se conservan los candidatos; en
+        // cualquier otro caso (p. ej. tras completar un directorio) se recalculan
+        let (base, prefix) = split_input(input);
+        if input != self.last_text || self.candidates.len() <= 1 {
+            self.candidates = list_candidates(&expand_home(base), prefix);
+            self.base = base.to_string();
+            self.selected = None;
+        }
+
+        let text = match self.candidates.as_slice() {
+            [] => return None,
+            [single] => format!("{}{}", base, single.display()),
+            candidates => {
+                let common = common_prefix(candidates);
+                if common.chars().count() > prefix.chars().count() {
+                    format!("{}{}", base, common)
+                } else {
+                    return Some(self.select(0));
+                }
+            }
+        };
+
+        self.last_text = text.clone();
+        Some(text)
+    }
+
+    fn select(&mut self, idx: usize) -> String {
+        self.selected = Some(idx);
+        self.last_text = format!("{}{}", self.base, self.candidates[idx].display());
+        self.last_text.clone()
+    }
+
+    /// Lista de candidatos para mostrar, con el seleccionado entre corchetes
+    pub fn summary(&self) -> String {
+        self.candidates
+            .iter()
+            .enumerate()
+            .map(|(idx, candidate)| {
+                if Some(idx) == self.selected {
+                    format!("[{}]", candidate.display())
+                } else {
+                    candidate.display()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+}
+
+/// Expande `~` al directorio personal del usuario
+pub fn expand_home(input: &str) -> PathBuf {
+    let path = PathBuf::from(input);
+    let home_dir = env::var_os("HOME").map(PathBuf::from);
+
+    expand_home_path(&path, home_dir.as_deref()).unwrap_or(path)
+}
+
+/// Separa lo escrito en el directorio (hasta la última `/`) y el prefijo del nombre
+fn split_input(input: &str) -> (&str, &str) {
+    if input == "~" {
+        return ("~/", "");
+    }
+
+    match input.rfind('/') {
+        Some(idx) => input.split_at(idx + 1),
+        None => ("", input),
+    }
+}
+
+/// Entradas de `dir` cuyo nombre empieza con `prefix`, ordenadas por nombre
+///
+/// Los archivos ocultos solo se incluyen si el prefijo empieza con `.`.
+fn list_candidates(dir: &Path, prefix: &str) -> Vec<Candidate> {
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut candidates: Vec<Candidate> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+
+            // `metadata` sigue los enlaces simbólicos a directorios
+            let is_dir = fs::metadata(entry.path())
+                .map(|meta| meta.is_dir())
+                .unwrap_or(false);
+            Some(Candidate { name, is_dir })
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| a.name.cmp(&b.name));
+    candidates
+}
+
+/// Prefijo más largo compartido por todos los candidatos
+fn common_prefix(candidates: &[Candidate]) -> String {
+    let Some((first, rest)) = candidates.split_first() else {
+        return String::new();
+    };
+
+    let mut common: Vec<char> = first.name.chars().collect();
+    for candidate in rest {
+        let shared = common
+            .iter()
+            .zip(candidate.name.chars())
+            .take_while(|(a, b)| **a == *b)
+            .count();
+        common.truncate(shared);
+    }
+
+    common.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_tree(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "hyperion_completion_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("salida.txt"), "").unwrap();
+        fs::write(dir.join("saludo.rs"), "").unwrap();
+        fs::write(dir.join(".oculto"), "").unwrap();
+        dir
+    }
+
+    #[test]
+    fn split_input_separates_directory_and_prefix() {
+        assert_eq!(split_input("src/ma"), ("src/", "ma"));
+        assert_eq!(split_input("main"), ("", "main"));
+        assert_eq!(split_input("/"), ("/", ""));
+        assert_eq!(split_input("~"), ("~/", ""));
+    }
+
+    #[test]
+    fn completes_common_prefix_then_cycles() {
+        let dir = temp_tree("cycle");
+        let base = format!("{}/", dir.display());
+        let mut completion = PathCompletion::new();
+
+        let text = completion.complete(&format!("{}sa", base)).unwrap();
+        assert_eq!(text, format!("{}sal", base));
+        assert_eq!(completion.candidates().len(), 2);
+        assert_eq!(completion.selected, None);
+
+        let text = completion.complete(&text).unwrap();
+        assert_eq!(text, format!("{}salida.txt", base));
+
+        let text = completion.complete(&text).unwrap();
+        assert_eq!(text, format!("{}saludo.rs", base));
+        assert_eq!(completion.summary(), "salida.txt  [saludo.rs]");
+
+        let text = completion.complete(&text).unwrap();
+        assert_eq!(text, format!("{}salida.txt", base));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn directories_get_a_trailing_slash() {
+        let dir = temp_tree("dirs");
+        let mut completion = PathCompletion::new();
+
+        assert_eq!(
+            completion.complete(&format!("{}/sr", dir.display())),
+            Some(format!("{}/src/", dir.display()))
+        );
+        fs::write(dir.join("src/main.rs"), "").unwrap();
+        assert_eq!(
+            completion.complete(&format!("{}/src/", dir.display())),
+            Some(format!("{}/src/main.rs", dir.display()))
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn cycling_keeps_the_directory_of_the_candidates() {
+        let dir = temp_tree("cycle_dirs");
+        let base = format!("{}/", dir.display());
+        let mut completion = PathCompletion::new();
+
+        assert_eq!(
+            completion.complete(&format!("{}s", base)),
+            Some(format!("{}salida.txt", base))
+        );
+        assert_eq!(
+            completion.complete(&format!("{}salida.txt", base)),
+            Some(format!("{}saludo.rs", base))
+        );
+        let text = completion.complete(&format!("{}saludo.rs", base)).unwrap();
+        assert_eq!(text, format!("{}src/", base));
+        assert_eq!(
+            completion.complete(&text),
+            Some(format!("{}salida.txt", base))
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn hidden_files_need_a_dot_prefix() {
+        let dir = temp_tree("hidden");
+
+        let names: Vec<String> = list_candidates(&dir, "")
+            .into_iter()
+            .map(|c| c.display())
+            .collect();
+        assert_eq!(names, ["salida.txt", "saludo.rs", "src/"]);
+        assert_eq!(list_candidates(&dir, ".").len(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn no_match_leaves_the_text_unchanged() {
+        let dir = temp_tree("none");
+        let mut completion = PathCompletion::new();
+
+        assert_eq!(completion.complete(&format!("{}/zzz", dir.display())), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -10,6 +10,18 @@ pub struct SyntaxTheme {
     pub string: Color,
     pub number: Color,
     pub comment: Color,
+    pub type_name: Color,
+    pub function: Color,
+    pub macro_call: Color,
+    pub attribute: Color,
+    pub lifetime: Color,
+    pub character: Color,
+    pub operator: Color,
+    pub heading: Color,
+    pub key: Color,
+    pub variable: Color,
+    pub emphasis: Color,
+    pub tag: Color,
 }
 
 impl SyntaxTheme {
@@ -19,8 +31,49 @@ impl SyntaxTheme {
             string: Color::Green,
             number: Color::Yellow,
             comment: Color::DarkGrey,
+            type_name: Color::Cyan,
+            function: Color::DarkYellow,
+            macro_call: Color::Magenta,
+            attribute: Color::DarkCyan,
+            lifetime: Color::DarkMagenta,
+            character: Color::DarkGreen,
+            operator: Color::Grey,
+            heading: Color::Red,
+            key: Color::Cyan,
+            variable: Color::DarkRed,
+            emphasis: Color::White,
+            tag: Color::Blue,
         }
     }
+
+    /// Asigna el color de la clave `key` de la sección `[syntax]`
+    ///
+    /// # Retorna
+    /// `false` si la clave no corresponde a ningún tipo de token
+    fn set_color(&mut self, key: &str, color: Color) -> bool {
+        let slot = match key {
+            "keyword" => &mut self.keyword,
+            "string" => &mut self.string,
+            "number" => &mut self.number,
+            "comment" => &mut self.comment,
+            "type" => &mut self.type_name,
+            "function" => &mut self.function,
+            "macro" => &mut self.macro_call,
+            "attribute" => &mut self.attribute,
+            "lifetime" => &mut self.lifetime,
+            "char" => &mut self.character,
+            "operator" => &mut self.operator,
+            "heading" => &mut self.heading,
+            "key" => &mut self.key,
+            "variable" => &mut self.variable,
+            "emphasis" => &mut self.emphasis,
+            "tag" => &mut self.tag,
+            _ => return false,
+        };
+
+        *slot = color;
+        true
+    }
 }
 
 impl Default for SyntaxTheme {
@@ -29,6 +82,65 @@ impl Default for SyntaxTheme {
     }
 }
 
+/// Opciones generales del editor (sección `[editor]`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EditorSettings {
+    /// Conservar la versión anterior del archivo como `archivo~` al guardar
+    pub backup: bool,
+
+    /// Guardar el historial de los prompts entre sesiones
+    pub history: bool,
+}
+
+pub fn load_editor_settings() -> EditorSettings {
+    find_config_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|content| parse_editor_settings(&content))
+        .unwrap_or_default()
+}
+
+fn parse_editor_settings(content: &str) -> EditorSettings {
+    let mut settings = EditorSettings::default();
+    let mut in_editor_section = false;
+
+    for raw_line in content.lines() {
+        let line = strip_inline_comment(raw_line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            let section = &line[1..line.len() - 1].trim();
+            in_editor_section = *section == "editor";
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        let Some(value) = parse_bool(value.trim()) else {
+            continue;
+        };
+
+        match (in_editor_section, key.trim()) {
+            (true, "backup") | (_, "editor.backup") => settings.backup = value,
+            (true, "history") | (_, "editor.history") => settings.history = value,
+            _ => {}
+        }
+    }
+
+    settings
+}
+
+fn parse_bool(input: &str) -> Option<bool> {
+    match input {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
 pub fn load_syntax_theme() -> SyntaxTheme {
     let theme = SyntaxTheme::default();
 
@@ -72,31 +184,21 @@ fn parse_syntax_theme(content: &str) -> Option<SyntaxTheme> {
             continue;
         };
 
-        match (in_syntax_section, key) {
-            (true, "keyword") | (_, "syntax.keyword") => {
-                theme.keyword = color;
-                parsed_any = true;
-            }
-            (true, "string") | (_, "syntax.string") => {
-                theme.string = color;
-                parsed_any = true;
-            }
-            (true, "number") | (_, "syntax.number") => {
-                theme.number = color;
-                parsed_any = true;
-            }
-            (true, "comment") | (_, "syntax.comment") => {
-                theme.comment = color;
-                parsed_any = true;
-            }
-            _ => {}
+        let key = match key.strip_prefix("syntax.") {
+            Some(key) => key,
+            None if in_syntax_section => key,
+            None => continue,
+        };
+
+        if theme.set_color(key, color) {
+            parsed_any = true;
         }
     }
 
     parsed_any.then_some(theme)
 }
 
-fn strip_inline_comment(line: &str) -> &str {
+pub(crate) fn strip_inline_comment(line: &str) -> &str {
     let mut in_single = false;
     let mut in_double = false;
     let mut prev_was_escape = false;
@@ -118,7 +220,7 @@ fn strip_inline_comment(line: &str) -> &str {
     line
 }
 
-fn find_config_path() -> Option<PathBuf> {
+pub(crate) fn find_config_path() -> Option<PathBuf> {
     let current_dir = env::current_dir().ok();
     let env_config = env::var_os("HYPERION_CONFIG").map(PathBuf::from);
     let xdg_config_home = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from);
@@ -175,7 +277,25 @@ fn find_config_path_with(
     None
 }
 
-fn expand_home_path(path: &Path, home_dir: Option<&Path>) -> Option<PathBuf> {
+/// Directorio con las definiciones de lenguajes del usuario
+/// (`$XDG_CONFIG_HOME/hyperion/languages` o `~/.config/hyperion/languages`)
+pub(crate) fn languages_dir() -> Option<PathBuf> {
+    let xdg_config_home = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from);
+    let home_dir = env::var_os("HOME").map(PathBuf::from);
+
+    languages_dir_with(xdg_config_home, home_dir)
+}
+
+fn languages_dir_with(
+    xdg_config_home: Option<PathBuf>,
+    home_dir: Option<PathBuf>,
+) -> Option<PathBuf> {
+    xdg_config_home
+        .map(|dir| dir.join("hyperion/languages"))
+        .or_else(|| home_dir.map(|home| home.join(".config/hyperion/languages")))
+}
+
+pub(crate) fn expand_home_path(path: &Path, home_dir: Option<&Path>) -> Option<PathBuf> {
     let home_dir = home_dir?;
     let raw = path.to_str()?;
 
@@ -317,6 +437,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_theme_with_extended_token_keys() {
+        let content = r##"
+            [syntax]
+            type = "#010101"
+            function = "#020202"
+            macro = "#030303"
+            attribute = "#040404"
+            lifetime = "#050505"
+            char = "#060606"
+            operator = "#070707"
+            heading = "#090909"
+            tag = "#0A0A0A"
+            desconocida = "#080808"
+        "##;
+
+        let theme = parse_syntax_theme(content).unwrap();
+        let gray = |v| Color::Rgb { r: v, g: v, b: v };
+
+        assert_eq!(theme.type_name, gray(1));
+        assert_eq!(theme.function, gray(2));
+        assert_eq!(theme.macro_call, gray(3));
+        assert_eq!(theme.attribute, gray(4));
+        assert_eq!(theme.lifetime, gray(5));
+        assert_eq!(theme.character, gray(6));
+        assert_eq!(theme.operator, gray(7));
+        assert_eq!(theme.heading, gray(9));
+        assert_eq!(theme.tag, gray(10));
+        assert_eq!(theme.keyword, SyntaxTheme::default().keyword);
+    }
+
+    #[test]
+    fn parse_editor_backup_setting() {
+        let content = r##"
+            [syntax]
+            backup = true
+
+            [editor]
+            backup = true # guardar archivo~
+        "##;
+
+        assert!(parse_editor_settings(content).backup);
+        assert!(parse_editor_settings("editor.backup = true").backup);
+        assert!(!parse_editor_settings("[syntax]\nbackup = true").backup);
+        assert!(!parse_editor_settings("[editor]\nbackup = si").backup);
+    }
+
+    #[test]
+    fn parse_editor_history_setting() {
+        let settings = parse_editor_settings("[editor]\nbackup = true\nhistory = true");
+
+        assert!(settings.history);
+        assert!(settings.backup);
+        assert!(!parse_editor_settings("[editor]\nbackup = true").history);
+    }
+
     #[test]
     fn find_config_path_respects_precedence() {
         let base = unique_temp_dir();
@@ -412,4 +588,20 @@ mod tests {
             .as_nanos();
         env::temp_dir().join(format!("hyperion_test_{nanos}"))
     }
+
+    #[test]
+    fn languages_dir_prefers_xdg_config_home() {
+        assert_eq!(
+            languages_dir_with(
+                Some(PathBuf::from("/cfg")),
+                Some(PathBuf::from("/home/ana"))
+            ),
+            Some(PathBuf::from("/cfg/hyperion/languages"))
+        );
+        assert_eq!(
+            languages_dir_with(None, Some(PathBuf::from("/home/ana"))),
+            Some(PathBuf::from("/home/ana/.config/hyperion/languages"))
+        );
+        assert_eq!(languages_dir_with(None, None), None);
+    }
 }
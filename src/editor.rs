@@ -3,30 +3,106 @@
 //! Contiene la estructura `Editor` que coordina todas las operaciones
 //! del editor incluyendo navegación, edición, búsqueda y renderizado.
 
-use std::io::Write;
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
 
 use crossterm::{cursor, terminal};
+use encoding_rs::Encoding;
 
 use crate::{
     buffer::TextBuffer,
-    config::{SyntaxTheme, load_syntax_theme},
-    search::SearchState,
+    command::Command,
+    config::{EditorSettings, SyntaxTheme, load_editor_settings, load_syntax_theme},
+    file_io::{self, FileStamp},
+    language::{Languages, load_languages},
+    search::{self, SearchState},
+    swap::{self, SwapData},
     terminal::messages,
     ui,
 };
 
-pub struct Editor {
+/// Posición del cursor y del scroll, para poder restaurar la vista
+#[derive(Clone, Copy)]
+struct ViewPosition {
+    cursor_x: usize,
+    cursor_y: usize,
+    offset_row: usize,
+    offset_col: usize,
+}
+
+/// Estado propio de cada archivo abierto
+///
+/// El buffer activo vive en `Editor::active`; los demás se guardan en
+/// `Editor::buffers` y se intercambian enteros al cambiar de buffer, así que
+/// todo lo que dependa del archivo abierto tiene que estar aquí.
+struct BufferSlot {
     buffer: TextBuffer,
     cursor_x: usize,
     cursor_y: usize,
-    pub filename: Option<String>,
-    pub state_msg: String,
-    window_sizes: (u16, u16),
+    filename: Option<String>,
     offset_row: usize,
     offset_col: usize,
     search: SearchState,
+    modified: bool,
+    selection_anchor: Option<(usize, usize)>,
+
+    /// Si se escribe el archivo de recuperación del buffer
+    swap_enabled: bool,
+
+    /// Revisión del buffer reflejada en el archivo de recuperación
+    swap_revision: u64,
+
+    /// Archivo de recuperación encontrado al abrir, pendiente de decisión
+    pending_recovery: Option<PathBuf>,
+
+    /// Huella del archivo en disco al abrirlo o guardarlo por última vez
+    disk_stamp: Option<FileStamp>,
+
+    /// Si el buffer rechaza las modificaciones
+    read_only: bool,
+}
+
+impl BufferSlot {
+    fn new() -> Self {
+        BufferSlot {
+            buffer: TextBuffer::new(),
+            cursor_x: 0,
+            cursor_y: 0,
+            filename: None,
+            offset_row: 0,
+            offset_col: 0,
+            search: SearchState::new(),
+            modified: false,
+            selection_anchor: None,
+            swap_enabled: true,
+            swap_revision: 0,
+            pending_recovery: None,
+            disk_stamp: None,
+            read_only: false,
+        }
+    }
+}
+
+pub struct Editor {
+    /// Estado del buffer activo; los demás esperan en `buffers`
+    active: BufferSlot,
+    pub state_msg: String,
+    window_sizes: (u16, u16),
     clipboard: String,
     syntax_theme: SyntaxTheme,
+
+    /// Lenguajes para el resaltado de sintaxis (incluidos y del usuario)
+    languages: Languages,
+    settings: EditorSettings,
+    search_origin: Option<ViewPosition>,
+    last_swap_write: Instant,
+
+    /// Buffers abiertos; el slot del buffer activo queda vacío mientras está activo
+    buffers: Vec<BufferSlot>,
+    active_buffer: usize,
 }
 
 impl Editor {
@@ -34,362 +110,1426 @@ impl Editor {
         let window_sizes = terminal::size().unwrap_or((80, 24));
 
         Editor {
-            buffer: TextBuffer::new(),
-            cursor_x: 0,
-            cursor_y: 0,
-            filename: None,
+            active: BufferSlot::new(),
             state_msg: messages::DEFAULT_STATUS.to_string(),
             window_sizes,
-            offset_row: 0,
-            offset_col: 0,
-            search: SearchState::new(),
             clipboard: String::new(),
             syntax_theme: load_syntax_theme(),
+            languages: load_languages(),
+            settings: load_editor_settings(),
+            search_origin: None,
+            last_swap_write: Instant::now(),
+            buffers: vec![BufferSlot::new()],
+            active_buffer: 0,
         }
     }
 
-    pub fn open_file(&mut self, path: &str) {
+    /// Abre un archivo en un buffer nuevo
+    ///
+    /// Si el archivo ya está abierto se cambia a su buffer; si el buffer
+    /// activo está vacío y sin nombre, se reutiliza.
+    ///
+    /// # Retorna
+    /// `true` si el archivo quedó en el buffer activo
+    pub fn open_file(&mut self, path: &str) -> bool {
+        if let Some(idx) = self.find_buffer(path) {
+            self.switch_buffer(idx);
+            return true;
+        }
+
         match TextBuffer::from_file(path) {
             Ok(buffer) => {
-                self.buffer = buffer;
-                self.filename = Some(path.to_string());
-                self.cursor_x = 0;
-                self.cursor_y = 0;
-                self.offset_row = 0;
-                self.offset_col = 0;
-                self.state_msg = format!("Archivo '{}' cargado correctamente", path);
+                if !self.is_blank_buffer() {
+                    self.buffers.push(BufferSlot::new());
+                    self.switch_buffer(self.buffers.len() - 1);
+                }
+                self.active.buffer = buffer;
+                self.active.filename = Some(path.to_string());
+                self.active.cursor_x = 0;
+                self.active.cursor_y = 0;
+                self.active.offset_row = 0;
+                self.active.offset_col = 0;
+                self.active.modified = false;
+                self.active.selection_anchor = None;
+                self.active.swap_revision = self.active.buffer.revision();
+                self.active.disk_stamp = FileStamp::read(Path::new(path)).ok();
+
+                let swap_file = swap::swap_path(Path::new(path));
+                self.active.swap_enabled = !swap_file.exists();
+                self.active.pending_recovery = swap_file.exists().then_some(swap_file);
+
+                self.active.read_only = !file_io::is_writable(Path::new(path));
+                self.state_msg = if self.active.buffer.has_mixed_line_endings() {
+                    // Guardar unifica los finales: se avisa antes de que pase
+                    format!(
+                        "Archivo '{}' con finales de línea mixtos: se guardará con {} (Ctrl+E lf / crlf para elegir)",
+                        path,
+                        self.active.buffer.line_ending().label()
+                    )
+                } else if self.active.read_only {
+                    format!(
+                        "Archivo '{}' cargado en solo lectura (sin permiso de escritura)",
+                        path
+                    )
+                } else {
+                    format!("Archivo '{}' cargado correctamente", path)
+                };
+                true
             }
             Err(e) => {
                 self.state_msg = format!("Error al abrir el archivo: {}", e);
+                false
             }
         }
     }
 
-    pub fn save_file(&mut self, path: &str) {
-        match self.buffer.save_to_file(path) {
+    /// Carga en un buffer sin nombre el contenido leído de la entrada estándar
+    ///
+    /// Al guardar se pide un nombre de archivo.
+    pub fn open_stdin(&mut self, bytes: &[u8]) {
+        if !self.is_blank_buffer() {
+            self.buffers.push(BufferSlot::new());
+            self.switch_buffer(self.buffers.len() - 1);
+        }
+
+        self.active.buffer = TextBuffer::from_bytes(bytes);
+        self.active.swap_revision = self.active.buffer.revision();
+        self.state_msg = format!(
+            "Leídas {} líneas de la entrada estándar (Ctrl+S para guardar)",
+            self.active.buffer.line_count().saturating_sub(1)
+        );
+    }
+
+    /// Nombre del archivo del buffer activo, si tiene
+    pub fn filename(&self) -> Option<&str> {
+        self.active.filename.as_deref()
+    }
+
+    /// Indica si `path` es el archivo del buffer activo
+    pub fn is_own_file(&self, path: &str) -> bool {
+        self.active
+            .filename
+            .as_deref()
+            .is_some_and(|name| same_file_key(name) == same_file_key(path))
+    }
+
+    /// Directorio del archivo actual con `/` final, como valor inicial del
+    /// prompt de abrir archivo (vacío si el buffer no tiene nombre)
+    pub fn current_dir_hint(&self) -> String {
+        self.active
+            .filename
+            .as_deref()
+            .and_then(|name| Path::new(name).parent())
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| format!("{}/", dir))
+            .unwrap_or_default()
+    }
+
+    /// Prepara un buffer para un archivo que todavía no existe en disco
+    pub fn new_file(&mut self, path: &str) {
+        if let Some(idx) = self.find_buffer(path) {
+            self.switch_buffer(idx);
+            return;
+        }
+
+        if !self.is_blank_buffer() {
+            self.buffers.push(BufferSlot::new());
+            self.switch_buffer(self.buffers.len() - 1);
+        }
+
+        self.active.filename = Some(path.to_string());
+        self.state_msg = format!("Nuevo archivo: '{}' (Ctrl+S para guardar)", path);
+    }
+
+    /// Guarda el buffer en `path`
+    ///
+    /// # Retorna
+    /// `true` si el archivo se guardó correctamente
+    pub fn save_file(&mut self, path: &str) -> bool {
+        // Solo lectura protege el archivo del buffer; guardar una copia en
+        // otra ruta está permitido
+        let own_file = self.is_own_file(path);
+        if own_file && !self.ensure_writable() {
+            return false;
+        }
+
+        match self.active.buffer.save_to_file(path, self.settings.backup) {
             Ok(_) => {
-                self.filename = Some(path.to_string());
+                if self.active.filename.as_deref() != Some(path) {
+                    self.remove_swap();
+                }
+                self.active.filename = Some(path.to_string());
+                self.active.buffer.mark_saved();
+                self.active.modified = false;
+                self.active.swap_enabled = true;
+                self.active.swap_revision = self.active.buffer.revision();
+                self.active.disk_stamp = FileStamp::read(Path::new(path)).ok();
+                self.active.read_only = self.active.read_only && own_file;
+                self.remove_swap();
                 self.state_msg = format!("Archivo '{}' guardado correctamente.", path);
+                true
             }
             Err(e) => {
-                self.state_msg = format!("Error al intentar guardar el archivo: {}", e);
+                self.state_msg = save_error_message(path, &e);
+                false
             }
         }
     }
 
-    pub fn insert_char(&mut self, c: char) {
-        self.buffer.insert_char(self.cursor_y, self.cursor_x, c);
-        self.cursor_x += 1;
+    /// Índice del buffer activo
+    pub fn active_buffer(&self) -> usize {
+        self.active_buffer
     }
 
-    pub fn new_line(&mut self) {
-        let (new_y, new_x) = self.buffer.split_line(self.cursor_y, self.cursor_x);
-        self.cursor_y = new_y;
-        self.cursor_x = new_x;
+    /// Nombres de los buffers abiertos, marcando los que tienen cambios
+    pub fn buffer_names(&self) -> Vec<String> {
+        (0..self.buffers.len())
+            .map(|idx| {
+                let (filename, modified) = if idx == self.active_buffer {
+                    (self.active.filename.as_deref(), self.active.modified)
+                } else {
+                    let slot = &self.buffers[idx];
+                    (slot.filename.as_deref(), slot.modified)
+                };
+
+                format!(
+                    "{}: {}{}",
+                    idx + 1,
+                    filename.unwrap_or("[Sin nombre]"),
+                    if modified { " [+]" } else { "" }
+                )
+            })
+            .collect()
     }
 
-    pub fn insert_tab(&mut self) {
-        const TAB_SPACES: &str = "    "; // 4 espacios
-        self.buffer
-            .insert_str(self.cursor_y, self.cursor_x, TAB_SPACES);
-        self.cursor_x += TAB_SPACES.chars().count();
+    /// Índices de los buffers con cambios sin guardar
+    pub fn modified_buffers(&self) -> Vec<usize> {
+        (0..self.buffers.len())
+            .filter(|&idx| {
+                if idx == self.active_buffer {
+                    self.active.modified
+                } else {
+                    self.buffers[idx].modified
+                }
+            })
+            .collect()
     }
 
-    pub fn delete_char(&mut self) {
-        if self.buffer.delete_char(self.cursor_y, self.cursor_x) {
-            self.cursor_x -= 1;
-        } else if self.cursor_y > 0 {
-            let prev_len = self.buffer.join_with_previous(self.cursor_y);
-            self.cursor_y -= 1;
-            self.cursor_x = prev_len;
+    /// Activa el buffer con índice `idx`, guardando el estado del actual
+    pub fn switch_buffer(&mut self, idx: usize) {
+        if idx >= self.buffers.len() || idx == self.active_buffer {
+            return;
         }
-    }
 
-    pub fn move_up(&mut self) {
-        if self.cursor_y > 0 {
-            self.cursor_y -= 1;
-            self.cursor_x = self.buffer.clamp_column(self.cursor_y, self.cursor_x);
-        }
+        self.write_swap();
+        self.search_origin = None;
+
+        // El slot vacío del buffer activo pasa a ser el del nuevo buffer activo
+        std::mem::swap(&mut self.active, &mut self.buffers[self.active_buffer]);
+        self.active_buffer = idx;
+        std::mem::swap(&mut self.active, &mut self.buffers[idx]);
+
+        self.state_msg = format!(
+            "Buffer {}/{}: {}",
+            idx + 1,
+            self.buffers.len(),
+            self.active.filename.as_deref().unwrap_or("[Sin nombre]")
+        );
     }
 
-    pub fn move_down(&mut self) {
-        if self.cursor_y < self.buffer.line_count() - 1 {
-            self.cursor_y += 1;
-            self.cursor_x = self.buffer.clamp_column(self.cursor_y, self.cursor_x);
+    /// Activa el buffer siguiente, volviendo al primero después del último
+    pub fn next_buffer(&mut self) {
+        if self.buffers.len() == 1 {
+            self.state_msg = "Solo hay un buffer abierto".to_string();
+            return;
         }
+        self.switch_buffer((self.active_buffer + 1) % self.buffers.len());
     }
 
-    pub fn move_left(&mut self) {
-        if self.cursor_x > 0 {
-            self.cursor_x -= 1;
-        } else if self.cursor_y > 0 {
-            self.cursor_y -= 1;
-            self.cursor_x = self.buffer.line_length(self.cursor_y);
+    /// Activa el buffer anterior, volviendo al último antes del primero
+    pub fn previous_buffer(&mut self) {
+        if self.buffers.len() == 1 {
+            self.state_msg = "Solo hay un buffer abierto".to_string();
+            return;
         }
+        let count = self.buffers.len();
+        self.switch_buffer((self.active_buffer + count - 1) % count);
     }
 
-    pub fn move_right(&mut self) {
-        let line_length = self.buffer.line_length(self.cursor_y);
-        if self.cursor_x < line_length {
-            self.cursor_x += 1;
-        } else if self.cursor_y < self.buffer.line_count() - 1 {
-            self.cursor_y += 1;
-            self.cursor_x = 0;
+    /// Cierra el buffer activo sin salir del editor, descartando sus cambios
+    ///
+    /// Si era el único buffer, queda un buffer vacío.
+    pub fn close_buffer(&mut self) {
+        let name = self
+            .active
+            .filename
+            .clone()
+            .unwrap_or_else(|| "[Sin nombre]".to_string());
+
+        self.remove_swap();
+        self.search_origin = None;
+
+        if self.buffers.len() == 1 {
+            self.active = BufferSlot::new();
+        } else {
+            self.buffers.remove(self.active_buffer);
+            self.active_buffer = self.active_buffer.min(self.buffers.len() - 1);
+            self.active =
+                std::mem::replace(&mut self.buffers[self.active_buffer], BufferSlot::new());
         }
+
+        self.state_msg = format!("Buffer '{}' cerrado", name);
     }
 
-    pub fn move_to_line_start(&mut self) {
-        self.cursor_x = 0;
+    /// Busca un buffer abierto para `path`
+    fn find_buffer(&self, path: &str) -> Option<usize> {
+        let wanted = same_file_key(path);
+
+        (0..self.buffers.len()).find(|&idx| {
+            let filename = if idx == self.active_buffer {
+                self.active.filename.as_deref()
+            } else {
+                self.buffers[idx].filename.as_deref()
+            };
+            filename.is_some_and(|name| same_file_key(name) == wanted)
+        })
     }
 
-    pub fn move_to_line_end(&mut self) {
-        self.cursor_x = self.buffer.line_length(self.cursor_y);
+    /// Indica si el buffer activo está vacío, sin nombre y sin cambios
+    fn is_blank_buffer(&self) -> bool {
+        self.active.filename.is_none()
+            && !self.active.modified
+            && self.active.buffer.line_count() == 1
+            && self.active.buffer.line_length(0) == 0
     }
 
-    pub fn move_page_up(&mut self) {
-        let page_size = self.window_sizes.1.saturating_sub(3).max(1) as usize;
-        self.cursor_y = self.cursor_y.saturating_sub(page_size);
-        self.cursor_x = self.buffer.clamp_column(self.cursor_y, self.cursor_x);
+    /// Ruta del archivo de recuperación del buffer actual, si tiene nombre
+    fn swap_file(&self) -> Option<PathBuf> {
+        self.active
+            .filename
+            .as_deref()
+            .map(|name| swap::swap_path(Path::new(name)))
     }
 
-    pub fn move_page_down(&mut self) {
-        let page_size = self.window_sizes.1.saturating_sub(3).max(1) as usize;
-        let max_row = self.buffer.line_count().saturating_sub(1);
-        self.cursor_y = (self.cursor_y + page_size).min(max_row);
-        self.cursor_x = self.buffer.clamp_column(self.cursor_y, self.cursor_x);
+    /// Escribe el archivo de recuperación si hubo cambios desde la última
+    /// escritura y pasó al menos `swap::SWAP_INTERVAL`
+    pub fn update_swap(&mut self) {
+        if self.last_swap_write.elapsed() >= swap::SWAP_INTERVAL {
+            self.write_swap();
+        }
     }
 
-    pub fn delete_forward_char(&mut self) {
-        let line_length = self.buffer.line_length(self.cursor_y);
+    /// Escribe el archivo de recuperación si hubo cambios desde la última escritura
+    fn write_swap(&mut self) {
+        let revision = self.active.buffer.revision();
+        if !self.active.swap_enabled || revision == self.active.swap_revision {
+            return;
+        }
 
-        if self.cursor_x < line_length || self.cursor_y < self.buffer.line_count() - 1 {
-            self.move_right();
-            self.delete_char();
+        let Some(path) = self.swap_file() else {
+            return;
+        };
+
+        self.active.swap_revision = revision;
+        self.last_swap_write = Instant::now();
+
+        if !self.active.modified {
+            self.remove_swap();
+            return;
+        }
+
+        let data = SwapData {
+            cursor: (self.active.cursor_y, self.active.cursor_x),
+            text: self.active.buffer.text(),
+        };
+        if let Err(e) = swap::write(&path, &data) {
+            self.state_msg = format!("No se pudo escribir el archivo de recuperación: {}", e);
         }
     }
 
-    pub fn adjust_scroll(&mut self) {
-        let visible_lines = self.window_sizes.1.saturating_sub(3) as usize;
+    /// Elimina el archivo de recuperación del buffer actual
+    ///
+    /// No hace nada si el archivo se conservó por decisión del usuario.
+    pub fn remove_swap(&mut self) {
+        if !self.active.swap_enabled {
+            return;
+        }
 
-        if self.cursor_y < self.offset_row {
-            self.offset_row = self.cursor_y;
+        if let Some(path) = self.swap_file() {
+            let _ = swap::remove(&path);
         }
+    }
+
+    /// Elimina los archivos de recuperación de todos los buffers, al salir
+    pub fn remove_all_swaps(&mut self) {
+        self.remove_swap();
 
-        if self.cursor_y >= self.offset_row + visible_lines {
-            self.offset_row = self.cursor_y - visible_lines + 1;
+        for slot in &self.buffers {
+            if let (true, Some(name)) = (slot.swap_enabled, slot.filename.as_deref()) {
+                let _ = swap::remove(&swap::swap_path(Path::new(name)));
+            }
         }
+    }
 
-        let line_num_digits = self.buffer.line_count().to_string().len();
-        let line_num_width = line_num_digits + 2;
-        let visible_cols = (self.window_sizes.0 as usize).saturating_sub(line_num_width);
+    /// Indica si al abrir el archivo se encontró un archivo de recuperación
+    pub fn has_pending_recovery(&self) -> bool {
+        self.active.pending_recovery.is_some()
+    }
 
-        if self.cursor_x < self.offset_col {
-            self.offset_col = self.cursor_x;
+    /// Diferencias entre el archivo en disco y el archivo de recuperación
+    pub fn recovery_diff(&self) -> Vec<String> {
+        let Some(path) = &self.active.pending_recovery else {
+            return Vec::new();
+        };
+
+        match swap::read(path) {
+            Ok(data) => {
+                let diff = swap::line_diff(&self.active.buffer.text(), &data.text);
+                if diff.is_empty() {
+                    vec!["(sin diferencias)".to_string()]
+                } else {
+                    diff
+                }
+            }
+            Err(e) => vec![format!("Error al leer la recuperación: {}", e)],
         }
+    }
 
-        if visible_cols == 0 {
-            self.offset_col = 0;
+    /// Carga el contenido del archivo de recuperación en el buffer
+    ///
+    /// El cambio se puede deshacer para volver a la versión en disco.
+    pub fn recover_from_swap(&mut self) {
+        if !self.ensure_writable() {
+            self.keep_recovery();
             return;
         }
 
-        if self.cursor_x >= self.offset_col + visible_cols {
-            self.offset_col = self.cursor_x - visible_cols + 1;
+        let Some(path) = self.active.pending_recovery.take() else {
+            return;
+        };
+
+        match swap::read(&path) {
+            Ok(data) => {
+                self.active
+                    .buffer
+                    .replace_text(&data.text, (self.active.cursor_y, self.active.cursor_x));
+                self.active.cursor_y = data.cursor.0.min(self.active.buffer.line_count() - 1);
+                self.active.cursor_x = self
+                    .active
+                    .buffer
+                    .clamp_column(self.active.cursor_y, data.cursor.1);
+                self.active.modified = true;
+                self.state_msg = "Cambios recuperados (Ctrl+S para guardar)".to_string();
+            }
+            Err(e) => {
+                self.state_msg = format!("Error al leer la recuperación: {}", e);
+            }
         }
+        self.active.swap_enabled = true;
     }
 
-    pub fn update_window_size(&mut self, width: u16, height: u16) {
-        self.window_sizes = (width, height);
+    /// Descarta el archivo de recuperación pendiente
+    pub fn delete_recovery(&mut self) {
+        let Some(path) = self.active.pending_recovery.take() else {
+            return;
+        };
 
-        let visible_lines = height.saturating_sub(3) as usize;
-        let line_count = self.buffer.line_count();
-        let max_visible_lines = visible_lines.max(1);
-        let max_offset_row = line_count.saturating_sub(max_visible_lines);
+        self.state_msg = match swap::remove(&path) {
+            Ok(_) => "Archivo de recuperación eliminado".to_string(),
+            Err(e) => format!("No se pudo eliminar la recuperación: {}", e),
+        };
+        self.active.swap_enabled = true;
+    }
 
-        if self.offset_row > self.cursor_y {
-            self.offset_row = self.cursor_y;
-        }
-        if self.offset_row > max_offset_row {
-            self.offset_row = max_offset_row;
+    /// Deja el archivo de recuperación intacto y no lo sobrescribe en esta sesión
+    pub fn keep_recovery(&mut self) {
+        if self.active.pending_recovery.take().is_some() {
+            self.state_msg =
+                "Archivo de recuperación conservado; no se actualizará en esta sesión".to_string();
         }
+    }
 
-        let line_num_width = ui::calculate_line_number_width(self.buffer.line_count());
-        let visible_cols = width.saturating_sub(line_num_width as u16).max(1) as usize;
-        let line_length = self.buffer.line_length(self.cursor_y);
-        let max_offset_col = line_length.saturating_sub(visible_cols);
+    /// Vuelve a leer el archivo actual interpretándolo con otra codificación
+    fn reopen_with_encoding(&mut self, encoding: &'static Encoding) {
+        let Some(path) = self.active.filename.clone() else {
+            self.state_msg = "El buffer no tiene un archivo asociado".to_string();
+            return;
+        };
 
-        if self.offset_col > self.cursor_x {
-            self.offset_col = self.cursor_x;
+        if self.active.modified {
+            self.state_msg = "Guarde los cambios antes de reabrir el archivo".to_string();
+            return;
         }
-        if self.offset_col > max_offset_col {
-            self.offset_col = max_offset_col;
+
+        match self.reload_buffer(&path, encoding) {
+            Ok(_) => {
+                self.state_msg = format!(
+                    "Archivo '{}' reabierto como {}",
+                    path,
+                    self.active.buffer.encoding_label()
+                );
+            }
+            Err(e) => {
+                self.state_msg = format!("Error al abrir el archivo: {}", e);
+            }
         }
     }
 
-    pub fn search(&mut self, query: &str) {
-        let lines: Vec<String> = self.buffer.iter_lines().collect();
-        let count = self.search.search(query, &lines);
+    /// Reemplaza el buffer por el contenido del archivo en disco,
+    /// conservando la posición del cursor en lo posible
+    fn reload_buffer(&mut self, path: &str, encoding: &'static Encoding) -> io::Result<()> {
+        let buffer = TextBuffer::from_file_with_encoding(path, encoding)?;
+
+        self.active.buffer = buffer;
+        self.active.selection_anchor = None;
+        self.active.modified = false;
+        self.active.disk_stamp = FileStamp::read(Path::new(path)).ok();
+        self.active.cursor_y = self
+            .active
+            .cursor_y
+            .min(self.active.buffer.line_count() - 1);
+        self.active.cursor_x = self
+            .active
+            .buffer
+            .clamp_column(self.active.cursor_y, self.active.cursor_x);
+        Ok(())
+    }
+
+    /// Indica si el archivo fue modificado en disco por otro programa
+    /// desde que se abrió o se guardó por última vez
+    ///
+    /// Si solo cambió la fecha pero no el contenido, se actualiza la huella
+    /// y no se considera un cambio.
+    pub fn changed_on_disk(&mut self) -> bool {
+        let (Some(path), Some(stamp)) = (self.active.filename.as_deref(), self.active.disk_stamp)
+        else {
+            return false;
+        };
+        let path = Path::new(path);
+
+        if stamp.matches_metadata(path) {
+            return false;
+        }
 
-        if query.is_empty() {
-            self.state_msg = messages::SEARCH_CANCELLED.to_string();
+        let Ok(current) = FileStamp::read(path) else {
+            // Si el archivo se borró, guardar simplemente lo vuelve a crear
+            return false;
+        };
+
+        if stamp.same_content(&current) {
+            self.active.disk_stamp = Some(current);
+            return false;
+        }
+
+        true
+    }
+
+    /// Descarta los cambios del editor y vuelve a leer el archivo del disco
+    pub fn reload_from_disk(&mut self) {
+        let Some(path) = self.active.filename.clone() else {
             return;
+        };
+
+        match self.reload_buffer(&path, self.active.buffer.encoding()) {
+            Ok(_) => {
+                self.remove_swap();
+                self.state_msg = format!("Archivo '{}' recargado desde el disco", path);
+            }
+            Err(e) => {
+                self.state_msg = format!("Error al recargar el archivo: {}", e);
+            }
         }
+    }
 
-        if count > 0 {
-            self.jump_to_current_match();
-            self.state_msg = format!("Encontradas {} coincidencias de '{}'", count, query);
-        } else {
-            self.state_msg = format!("No se encontró '{}'", query);
+    /// Acepta el cambio externo sin recargar; el próximo guardado lo sobrescribe
+    pub fn ignore_external_change(&mut self) {
+        if let Some(path) = self.active.filename.as_deref() {
+            self.active.disk_stamp = FileStamp::read(Path::new(path)).ok();
         }
     }
 
-    pub fn jump_to_current_match(&mut self) {
-        if let Some(m) = self.search.current_match() {
-            self.cursor_y = m.line;
-            self.cursor_x = m.start_col;
-            if let Some(idx) = self.search.current_index() {
+    /// Guarda la versión del editor en una copia y recarga el archivo del disco
+    pub fn keep_both_versions(&mut self) {
+        let Some(path) = self.active.filename.clone() else {
+            return;
+        };
+
+        let copy = conflict_copy_path(Path::new(&path));
+        let copy_name = copy.to_string_lossy().into_owned();
+
+        if let Err(e) = self.active.buffer.save_to_file(&copy_name, false) {
+            self.state_msg = save_error_message(&copy_name, &e);
+            return;
+        }
+
+        self.reload_from_disk();
+        self.state_msg = format!(
+            "Sus cambios se guardaron en '{}'; '{}' se recargó del disco",
+            copy_name, path
+        );
+    }
+
+    /// Ejecuta un comando del prompt de comandos, ya interpretado con
+    /// `command::parse_command`
+    pub fn execute_command(&mut self, command: Command) {
+        match command {
+            Command::SetLineEnding(_) | Command::SetEncoding(_) if !self.ensure_writable() => {}
+            Command::SetLineEnding(line_ending) => {
+                if self.active.buffer.line_ending() != line_ending {
+                    self.active.modified = true;
+                }
+                // También confirma el estilo de un archivo con finales mixtos
+                self.active.buffer.set_line_ending(line_ending);
+                self.state_msg = format!("Fin de línea: {}", line_ending.label());
+            }
+            Command::SetEncoding(encoding) => {
+                if self.active.buffer.encoding() != encoding {
+                    self.active.buffer.set_encoding(encoding);
+                    self.active.modified = true;
+                }
                 self.state_msg = format!(
-                    "Coincidencia {}/{}: '{}'",
-                    idx + 1,
-                    self.search.match_count(),
-                    self.search.query().unwrap_or(&String::new())
+                    "Codificación: {} (Ctrl+S para guardar)",
+                    self.active.buffer.encoding_label()
                 );
             }
+            Command::ReopenWithEncoding(encoding) => self.reopen_with_encoding(encoding),
+            Command::ToggleReadOnly => self.set_read_only(!self.active.read_only),
+            // Necesita la terminal para la lista de resultados: lo ejecuta `main`
+            Command::SearchInFiles(_) => {
+                self.state_msg = "La búsqueda en archivos no está disponible aquí".to_string();
+            }
         }
     }
 
-    pub fn next_match(&mut self) {
-        if self.search.next_match().is_some() {
-            self.jump_to_current_match();
-        } else {
-            self.state_msg = messages::NO_ACTIVE_SEARCH.to_string();
+    /// Indica si el buffer tiene cambios sin guardar
+    pub fn is_modified(&self) -> bool {
+        self.active.modified
+    }
+
+    /// Opciones de la sección `[editor]` de la configuración
+    pub fn settings(&self) -> EditorSettings {
+        self.settings
+    }
+
+    /// Avisa en la barra de estado si se ignoró alguna definición de lenguaje del usuario
+    pub fn report_language_errors(&mut self) {
+        let errors = self.languages.errors();
+        if let Some(first) = errors.first() {
+            let more = match errors.len() - 1 {
+                0 => String::new(),
+                n => format!(" (y {} más)", n),
+            };
+            self.state_msg = format!("Lenguaje ignorado: {}{}", first, more);
         }
     }
 
-    pub fn previous_match(&mut self) {
-        if self.search.previous_match().is_some() {
-            self.jump_to_current_match();
+    /// Indica si el buffer activo es de solo lectura
+    pub fn is_read_only(&self) -> bool {
+        self.active.read_only
+    }
+
+    /// Activa o desactiva el modo de solo lectura del buffer activo
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.active.read_only = read_only;
+        self.state_msg = if read_only {
+            "Modo solo lectura activado".to_string()
         } else {
-            self.state_msg = messages::NO_ACTIVE_SEARCH.to_string();
+            "Modo solo lectura desactivado".to_string()
+        };
+    }
+
+    /// Verifica que el buffer se pueda modificar, avisando en la barra de estado si no
+    fn ensure_writable(&mut self) -> bool {
+        if self.active.read_only {
+            self.state_msg = messages::READ_ONLY.to_string();
         }
+        !self.active.read_only
     }
 
-    pub fn go_to_line(&mut self, coords: (usize, usize)) {
-        if !self.buffer.is_valid_line(coords.0) {
-            self.state_msg = format!(
-                "Línea {} no existe. El documento tiene {} líneas",
-                coords.0 + 1,
-                self.buffer.line_count()
-            );
+    pub fn insert_char(&mut self, c: char) {
+        if !self.ensure_writable() {
             return;
         }
+        self.edit_over_selection(|editor| {
+            editor
+                .active
+                .buffer
+                .insert_char(editor.active.cursor_y, editor.active.cursor_x, c);
+            editor.active.cursor_x += 1;
+            editor.active.modified = true;
+        });
+    }
 
-        self.cursor_y = coords.0;
-        let line_length = self.buffer.line_length(self.cursor_y);
-
-        if coords.1 >= line_length {
-            self.cursor_x = line_length;
-            self.state_msg = format!(
-                "Columna {} fuera de rango. Posicionado al final de la línea (columna {})",
-                coords.1 + 1,
-                line_length
-            );
-        } else {
-            self.cursor_x = coords.1;
-            self.state_msg = format!(
-                "Posicionado en línea {}, columna {}",
-                self.cursor_y + 1,
-                self.cursor_x + 1
-            );
+    pub fn new_line(&mut self) {
+        if !self.ensure_writable() {
+            return;
         }
+        self.edit_over_selection(|editor| {
+            let (new_y, new_x) = editor
+                .active
+                .buffer
+                .split_line(editor.active.cursor_y, editor.active.cursor_x);
+            editor.active.cursor_y = new_y;
+            editor.active.cursor_x = new_x;
+            editor.active.modified = true;
+        });
     }
 
-    pub fn copy_line(&mut self) {
-        self.clipboard = self.buffer.line(self.cursor_y);
-        if self.clipboard.is_empty() {
-            self.state_msg = "Línea vacía copiada".to_string();
-        } else {
-            self.state_msg = "Línea copiada".to_string();
+    pub fn insert_tab(&mut self) {
+        const TAB_SPACES: &str = "    "; // 4 espacios
+        if !self.ensure_writable() {
+            return;
         }
+        self.edit_over_selection(|editor| {
+            editor.active.buffer.insert_str(
+                editor.active.cursor_y,
+                editor.active.cursor_x,
+                TAB_SPACES,
+            );
+            editor.active.cursor_x += TAB_SPACES.chars().count();
+            editor.active.modified = true;
+        });
     }
 
-    pub fn paste_clipboard(&mut self) {
-        if self.clipboard.is_empty() {
-            self.state_msg = "Portapapeles vacío".to_string();
+    pub fn delete_char(&mut self) {
+        if !self.ensure_writable() {
             return;
         }
 
-        let lines: Vec<&str> = self.clipboard.split('\n').collect();
-        self.buffer
-            .insert_str(self.cursor_y, self.cursor_x, &self.clipboard);
+        if self.delete_selection() {
+            return;
+        }
 
-        if lines.len() == 1 {
-            self.cursor_x += lines[0].chars().count();
-        } else {
-            self.cursor_y += lines.len() - 1;
-            self.cursor_x = lines.last().unwrap_or(&"").chars().count();
+        if self
+            .active
+            .buffer
+            .delete_char(self.active.cursor_y, self.active.cursor_x)
+        {
+            self.active.cursor_x -= 1;
+            self.active.modified = true;
+        } else if self.active.cursor_y > 0 {
+            let prev_len = self.active.buffer.join_with_previous(self.active.cursor_y);
+            self.active.cursor_y -= 1;
+            self.active.cursor_x = prev_len;
+            self.active.modified = true;
         }
     }
 
-    pub fn write<W: Write>(&self, stdout: &mut W) {
-        let mut out: Vec<u8> = Vec::with_capacity(16 * 1024);
+    pub fn move_up(&mut self) {
+        if self.active.cursor_y > 0 {
+            self.active.cursor_y -= 1;
+            self.active.cursor_x = self
+                .active
+                .buffer
+                .clamp_column(self.active.cursor_y, self.active.cursor_x);
+        }
+    }
 
-        write!(out, "{}", cursor::Hide).unwrap();
-        write!(out, "{}", cursor::MoveTo(0, 0)).unwrap();
-        write!(
-            out,
-            "{}",
-            terminal::Clear(terminal::ClearType::FromCursorDown)
-        )
-        .unwrap();
+    pub fn move_down(&mut self) {
+        if self.active.cursor_y < self.active.buffer.line_count() - 1 {
+            self.active.cursor_y += 1;
+            self.active.cursor_x = self
+                .active
+                .buffer
+                .clamp_column(self.active.cursor_y, self.active.cursor_x);
+        }
+    }
 
-        let visible_lines = self.window_sizes.1.saturating_sub(3) as usize;
+    pub fn move_left(&mut self) {
+        if self.active.cursor_x > 0 {
+            self.active.cursor_x -= 1;
+        } else if self.active.cursor_y > 0 {
+            self.active.cursor_y -= 1;
+            self.active.cursor_x = self.active.buffer.line_length(self.active.cursor_y);
+        }
+    }
 
-        if visible_lines == 0 || self.window_sizes.0 == 0 {
-            ui::render_message(&mut out, 0, "Ventana demasiado pequeña");
-            write!(out, "{}", cursor::Show).unwrap();
-            stdout.write_all(&out).unwrap();
-            stdout.flush().unwrap();
-            return;
+    pub fn move_right(&mut self) {
+        let line_length = self.active.buffer.line_length(self.active.cursor_y);
+        if self.active.cursor_x < line_length {
+            self.active.cursor_x += 1;
+        } else if self.active.cursor_y < self.active.buffer.line_count() - 1 {
+            self.active.cursor_y += 1;
+            self.active.cursor_x = 0;
         }
-        let line_num_width = ui::calculate_line_number_width(self.buffer.line_count());
-        let language = ui::language_from_filename(self.filename.as_deref());
+    }
 
-        let start = self.offset_row;
-        let end = (self.offset_row + visible_lines).min(self.buffer.line_count());
+    pub fn move_to_line_start(&mut self) {
+        self.active.cursor_x = 0;
+    }
 
-        for i in start..end {
-            let line_num = i + 1;
-            let window_row = (i - self.offset_row) as u16;
-            let _line_num_digits = self.buffer.line_count().to_string().len();
+    pub fn move_to_line_end(&mut self) {
+        self.active.cursor_x = self.active.buffer.line_length(self.active.cursor_y);
+    }
+
+    pub fn move_page_up(&mut self) {
+        let page_size = self.window_sizes.1.saturating_sub(3).max(1) as usize;
+        self.active.cursor_y = self.active.cursor_y.saturating_sub(page_size);
+        self.active.cursor_x = self
+            .active
+            .buffer
+            .clamp_column(self.active.cursor_y, self.active.cursor_x);
+    }
+
+    pub fn move_page_down(&mut self) {
+        let page_size = self.window_sizes.1.saturating_sub(3).max(1) as usize;
+        let max_row = self.active.buffer.line_count().saturating_sub(1);
+        self.active.cursor_y = (self.active.cursor_y + page_size).min(max_row);
+        self.active.cursor_x = self
+            .active
+            .buffer
+            .clamp_column(self.active.cursor_y, self.active.cursor_x);
+    }
+
+    pub fn delete_forward_char(&mut self) {
+        if !self.ensure_writable() {
+            return;
+        }
+
+        if self.delete_selection() {
+            return;
+        }
+
+        let line_length = self.active.buffer.line_length(self.active.cursor_y);
+
+        if self.active.cursor_x < line_length
+            || self.active.cursor_y < self.active.buffer.line_count() - 1
+        {
+            self.active
+                .buffer
+                .begin_undo_group((self.active.cursor_y, self.active.cursor_x));
+            self.move_right();
+            self.delete_char();
+            self.active
+                .buffer
+                .end_undo_group((self.active.cursor_y, self.active.cursor_x));
+        }
+    }
+
+    /// Aplica un movimiento del cursor
+    ///
+    /// Si `selecting` es verdadero la selección se extiende desde la posición
+    /// actual; en caso contrario cualquier selección activa se descarta.
+    pub fn move_with_selection(&mut self, movement: fn(&mut Self), selecting: bool) {
+        if selecting {
+            if self.active.selection_anchor.is_none() {
+                self.active.selection_anchor = Some((self.active.cursor_y, self.active.cursor_x));
+            }
+        } else {
+            self.active.selection_anchor = None;
+        }
+
+        movement(self);
+    }
+
+    /// Obtiene la selección activa ordenada como (inicio, fin)
+    ///
+    /// # Retorna
+    /// `None` si no hay selección o si está vacía
+    pub fn selection_range(&self) -> Option<((usize, usize), (usize, usize))> {
+        let anchor = self.active.selection_anchor?;
+        let cursor = (self.active.cursor_y, self.active.cursor_x);
+
+        match anchor.cmp(&cursor) {
+            std::cmp::Ordering::Less => Some((anchor, cursor)),
+            std::cmp::Ordering::Greater => Some((cursor, anchor)),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.active.selection_anchor = None;
+    }
+
+    /// Elimina el texto seleccionado y deja el cursor al inicio de la selección
+    ///
+    /// # Retorna
+    /// `true` si había una selección que eliminar
+    fn delete_selection(&mut self) -> bool {
+        let Some((start, end)) = self.selection_range() else {
+            self.active.selection_anchor = None;
+            return false;
+        };
+
+        self.active.buffer.delete_range(start, end);
+        self.active.cursor_y = start.0;
+        self.active.cursor_x = start.1;
+        self.active.selection_anchor = None;
+        self.active.modified = true;
+        true
+    }
+
+    /// Ejecuta una edición que reemplaza la selección activa, si existe,
+    /// en un único paso de deshacer
+    fn edit_over_selection(&mut self, edit: impl FnOnce(&mut Self)) {
+        if self.selection_range().is_none() {
+            self.active.selection_anchor = None;
+            edit(self);
+            return;
+        }
+
+        self.active
+            .buffer
+            .begin_undo_group((self.active.cursor_y, self.active.cursor_x));
+        self.delete_selection();
+        edit(self);
+        self.active
+            .buffer
+            .end_undo_group((self.active.cursor_y, self.active.cursor_x));
+    }
+
+    pub fn undo(&mut self) {
+        if !self.ensure_writable() {
+            return;
+        }
+        self.active.selection_anchor = None;
+        match self.active.buffer.undo() {
+            Some((line, col)) => {
+                self.active.cursor_y = line;
+                self.active.cursor_x = col;
+                self.active.modified = !self.active.buffer.is_saved();
+                self.state_msg = messages::UNDONE.to_string();
+            }
+            None => {
+                self.state_msg = messages::NOTHING_TO_UNDO.to_string();
+            }
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if !self.ensure_writable() {
+            return;
+        }
+        self.active.selection_anchor = None;
+        match self.active.buffer.redo() {
+            Some((line, col)) => {
+                self.active.cursor_y = line;
+                self.active.cursor_x = col;
+                self.active.modified = !self.active.buffer.is_saved();
+                self.state_msg = messages::REDONE.to_string();
+            }
+            None => {
+                self.state_msg = messages::NOTHING_TO_REDO.to_string();
+            }
+        }
+    }
+
+    pub fn adjust_scroll(&mut self) {
+        let visible_lines = self.window_sizes.1.saturating_sub(3) as usize;
+
+        if self.active.cursor_y < self.active.offset_row {
+            self.active.offset_row = self.active.cursor_y;
+        }
+
+        if self.active.cursor_y >= self.active.offset_row + visible_lines {
+            self.active.offset_row = self.active.cursor_y - visible_lines + 1;
+        }
+
+        let line_num_digits = self.active.buffer.line_count().to_string().len();
+        let line_num_width = line_num_digits + 2;
+        let visible_cols = (self.window_sizes.0 as usize).saturating_sub(line_num_width);
+
+        if self.active.cursor_x < self.active.offset_col {
+            self.active.offset_col = self.active.cursor_x;
+        }
+
+        if visible_cols == 0 {
+            self.active.offset_col = 0;
+            return;
+        }
+
+        if self.active.cursor_x >= self.active.offset_col + visible_cols {
+            self.active.offset_col = self.active.cursor_x - visible_cols + 1;
+        }
+    }
+
+    pub fn update_window_size(&mut self, width: u16, height: u16) {
+        self.window_sizes = (width, height);
+
+        let visible_lines = height.saturating_sub(3) as usize;
+        let line_count = self.active.buffer.line_count();
+        let max_visible_lines = visible_lines.max(1);
+        let max_offset_row = line_count.saturating_sub(max_visible_lines);
+
+        if self.active.offset_row > self.active.cursor_y {
+            self.active.offset_row = self.active.cursor_y;
+        }
+        if self.active.offset_row > max_offset_row {
+            self.active.offset_row = max_offset_row;
+        }
+
+        let line_num_width = ui::calculate_line_number_width(self.active.buffer.line_count());
+        let visible_cols = width.saturating_sub(line_num_width as u16).max(1) as usize;
+        let line_length = self.active.buffer.line_length(self.active.cursor_y);
+        let max_offset_col = line_length.saturating_sub(visible_cols);
+
+        if self.active.offset_col > self.active.cursor_x {
+            self.active.offset_col = self.active.cursor_x;
+        }
+        if self.active.offset_col > max_offset_col {
+            self.active.offset_col = max_offset_col;
+        }
+    }
+
+    /// Busca en el documento el texto ingresado en el prompt
+    ///
+    /// El texto puede empezar con prefijos de opciones (`re:`, `i:`, `s:`, `w:`),
+    /// ver [`search::parse_query`].
+    pub fn search(&mut self, input: &str) {
+        let (options, query) = search::parse_query(input);
+        self.active.search.set_options(options);
+
+        let lines: Vec<String> = self.active.buffer.iter_lines().collect();
+        let count = self.active.search.search(query, &lines);
+
+        if query.is_empty() {
+            self.state_msg = messages::SEARCH_CANCELLED.to_string();
+            return;
+        }
+
+        if let Some(error) = self.active.search.error() {
+            self.state_msg = format!("Expresión regular inválida: {}", error);
+            return;
+        }
+
+        if count > 0 {
+            self.active
+                .search
+                .select_nearest(self.active.cursor_y, self.active.cursor_x);
+            self.jump_to_current_match();
+            self.state_msg = format!("Encontradas {} coincidencias de '{}'", count, query);
+        } else {
+            self.state_msg = format!("No se encontró '{}'", query);
+        }
+    }
+
+    /// Inicia una búsqueda incremental, recordando la vista actual para
+    /// poder restaurarla si el usuario cancela
+    pub fn begin_incremental_search(&mut self) {
+        self.search_origin = Some(self.view_position());
+    }
+
+    /// Vuelve a ejecutar la búsqueda incremental con el texto actual del
+    /// prompt y salta a la coincidencia más cercana después del cursor original
+    pub fn update_incremental_search(&mut self, input: &str) {
+        let origin = *self.search_origin.get_or_insert(self.view_position());
+        self.restore_view(origin);
+
+        let (options, query) = search::parse_query(input);
+        self.active.search.set_options(options);
+        let lines: Vec<String> = self.active.buffer.iter_lines().collect();
+        let count = self.active.search.search(query, &lines);
+
+        if query.is_empty() {
+            self.state_msg = String::new();
+        } else if let Some(error) = self.active.search.error() {
+            self.state_msg = format!("Expresión regular inválida: {}", error);
+        } else if count == 0 {
+            self.state_msg = format!("No se encontró '{}'", query);
+        } else {
+            self.active
+                .search
+                .select_nearest(origin.cursor_y, origin.cursor_x);
+            self.jump_to_current_match();
+            self.adjust_scroll();
+        }
+    }
+
+    /// Termina la búsqueda incremental
+    ///
+    /// # Argumentos
+    /// * `input` - Texto confirmado con Enter, o `None` si se canceló con Esc
+    pub fn finish_incremental_search(&mut self, input: Option<&str>) {
+        let origin = self.search_origin.take();
+
+        let Some(input) = input.filter(|input| !search::parse_query(input).1.is_empty()) else {
+            if let Some(origin) = origin {
+                self.restore_view(origin);
+            }
+            self.active.search.search("", &[]);
+            self.state_msg = messages::SEARCH_CANCELLED.to_string();
+            return;
+        };
+
+        let (_, query) = search::parse_query(input);
+        if self.active.search.error().is_none() && self.active.search.match_count() > 0 {
+            self.state_msg = format!(
+                "Encontradas {} coincidencias de '{}'",
+                self.active.search.match_count(),
+                query
+            );
+        }
+    }
+
+    fn view_position(&self) -> ViewPosition {
+        ViewPosition {
+            cursor_x: self.active.cursor_x,
+            cursor_y: self.active.cursor_y,
+            offset_row: self.active.offset_row,
+            offset_col: self.active.offset_col,
+        }
+    }
+
+    fn restore_view(&mut self, view: ViewPosition) {
+        self.active.cursor_x = view.cursor_x;
+        self.active.cursor_y = view.cursor_y;
+        self.active.offset_row = view.offset_row;
+        self.active.offset_col = view.offset_col;
+    }
+
+    /// Resalta las coincidencias de `input` en el buffer activo y se
+    /// posiciona en la que empieza en `(line, column)`
+    ///
+    /// Se usa al abrir un resultado de la búsqueda en archivos.
+    pub fn show_search_hit(&mut self, input: &str, line: usize, column: usize) {
+        let (options, query) = search::parse_query(input);
+        self.active.search.set_options(options);
+        let lines: Vec<String> = self.active.buffer.iter_lines().collect();
+
+        if self.active.search.search(query, &lines) == 0 {
+            self.go_to_line((line, column));
+            return;
+        }
+
+        self.active.search.select_nearest(line, column);
+        self.jump_to_current_match();
+    }
+
+    pub fn jump_to_current_match(&mut self) {
+        if let Some(m) = self.active.search.current_match() {
+            self.active.selection_anchor = None;
+            self.active.cursor_y = m.line;
+            self.active.cursor_x = m.start_col;
+            if let Some(idx) = self.active.search.current_index() {
+                let label = self.active.search.options().label();
+                let options = if label.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", label)
+                };
+                self.state_msg = format!(
+                    "Coincidencia {}/{}: '{}'{}",
+                    idx + 1,
+                    self.active.search.match_count(),
+                    self.active.search.query().unwrap_or(&String::new()),
+                    options
+                );
+            }
+        }
+    }
+
+    /// Cantidad de coincidencias de la búsqueda activa
+    pub fn match_count(&self) -> usize {
+        self.active.search.match_count()
+    }
+
+    /// Reemplaza la coincidencia actual y avanza a la siguiente
+    ///
+    /// # Retorna
+    /// `true` si había una coincidencia que reemplazar
+    pub fn replace_current(&mut self, replacement: &str) -> bool {
+        if !self.ensure_writable() {
+            return false;
+        }
+
+        if !self.prepare_replacement(replacement) {
+            return false;
+        }
+
+        let Some(m) = self.active.search.current_match().cloned() else {
+            return false;
+        };
+
+        let text = m.replacement.clone().unwrap_or_default();
+        let text_len = text.chars().count();
+
+        self.active
+            .buffer
+            .begin_undo_group((self.active.cursor_y, self.active.cursor_x));
+        self.active
+            .buffer
+            .delete_range((m.line, m.start_col), (m.line, m.end_col));
+        self.active.buffer.insert_str(m.line, m.start_col, &text);
+        self.active
+            .buffer
+            .end_undo_group((m.line, m.start_col + text_len));
+
+        self.active.search.apply_replacement(text_len);
+        self.active.selection_anchor = None;
+        self.active.cursor_y = m.line;
+        self.active.cursor_x = m.start_col + text_len;
+        self.active.modified = true;
+        true
+    }
+
+    /// Calcula el reemplazo de todas las coincidencias sobre el texto actual
+    ///
+    /// Tiene que llamarse antes del primer reemplazo; después no hace nada
+    /// mientras el reemplazo sea el mismo (ver `SearchState::prepare_replacement`).
+    ///
+    /// # Retorna
+    /// `false` si no se pudo calcular, con el error en el mensaje de estado
+    pub fn prepare_replacement(&mut self, replacement: &str) -> bool {
+        if self.active.search.is_prepared(replacement) {
+            return true;
+        }
+
+        let lines: Vec<String> = self.active.buffer.iter_lines().collect();
+        match self.active.search.prepare_replacement(replacement, &lines) {
+            Ok(()) => true,
+            Err(error) => {
+                self.state_msg = error;
+                false
+            }
+        }
+    }
+
+    /// Reemplaza hasta `count` coincidencias a partir de la actual en un
+    /// único paso de deshacer
+    ///
+    /// # Retorna
+    /// La cantidad de reemplazos realizados
+    pub fn replace_matches(&mut self, replacement: &str, count: usize) -> usize {
+        let mut remaining = count;
+        let mut replaced = 0;
+
+        self.active
+            .buffer
+            .begin_undo_group((self.active.cursor_y, self.active.cursor_x));
+        while remaining > 0 {
+            let before = self.active.search.match_count();
+            if !self.replace_current(replacement) {
+                break;
+            }
+            remaining = remaining.saturating_sub(before - self.active.search.match_count());
+            replaced += 1;
+        }
+        self.active
+            .buffer
+            .end_undo_group((self.active.cursor_y, self.active.cursor_x));
+
+        self.state_msg = format!("Reemplazadas {} coincidencias", replaced);
+        replaced
+    }
+
+    pub fn next_match(&mut self) {
+        if self.active.search.next_match().is_some() {
+            self.jump_to_current_match();
+        } else {
+            self.state_msg = messages::NO_ACTIVE_SEARCH.to_string();
+        }
+    }
+
+    pub fn previous_match(&mut self) {
+        if self.active.search.previous_match().is_some() {
+            self.jump_to_current_match();
+        } else {
+            self.state_msg = messages::NO_ACTIVE_SEARCH.to_string();
+        }
+    }
+
+    pub fn go_to_line(&mut self, coords: (usize, usize)) {
+        if !self.active.buffer.is_valid_line(coords.0) {
+            self.state_msg = format!(
+                "Línea {} no existe. El documento tiene {} líneas",
+                coords.0 + 1,
+                self.active.buffer.line_count()
+            );
+            return;
+        }
+
+        self.active.cursor_y = coords.0;
+        let line_length = self.active.buffer.line_length(self.active.cursor_y);
+
+        if coords.1 >= line_length {
+            self.active.cursor_x = line_length;
+            self.state_msg = format!(
+                "Columna {} fuera de rango. Posicionado al final de la línea (columna {})",
+                coords.1 + 1,
+                line_length
+            );
+        } else {
+            self.active.cursor_x = coords.1;
+            self.state_msg = format!(
+                "Posicionado en línea {}, columna {}",
+                self.active.cursor_y + 1,
+                self.active.cursor_x + 1
+            );
+        }
+    }
+
+    /// Copia la selección activa o, si no hay selección, la línea actual
+    pub fn copy(&mut self) {
+        match self.selection_range() {
+            Some((start, end)) => {
+                self.clipboard = self.active.buffer.text_range(start, end);
+                self.state_msg = "Selección copiada".to_string();
+            }
+            None => self.copy_line(),
+        }
+    }
+
+    /// Corta la selección activa o, si no hay selección, la línea actual
+    pub fn cut(&mut self) {
+        if !self.ensure_writable() {
+            return;
+        }
+
+        if let Some((start, end)) = self.selection_range() {
+            self.clipboard = self.active.buffer.text_range(start, end);
+            self.delete_selection();
+            self.state_msg = "Selección cortada".to_string();
+            return;
+        }
+
+        // El portapapeles lleva el salto de línea que se borró con la línea,
+        // así pegar en la posición en que queda el cursor la restaura
+        let line_length = self.active.buffer.line_length(self.active.cursor_y);
+        let (start, end) = if self.active.cursor_y + 1 < self.active.buffer.line_count() {
+            ((self.active.cursor_y, 0), (self.active.cursor_y + 1, 0))
+        } else if self.active.cursor_y > 0 {
+            // En la última línea se borra el salto anterior y el cursor queda
+            // al final de la línea de arriba
+            let prev_length = self.active.buffer.line_length(self.active.cursor_y - 1);
+            (
+                (self.active.cursor_y - 1, prev_length),
+                (self.active.cursor_y, line_length),
+            )
+        } else {
+            ((0, 0), (0, line_length))
+        };
+
+        self.clipboard = self.active.buffer.text_range(start, end);
+        self.active.buffer.delete_range(start, end);
+        self.active.cursor_y = start.0;
+        self.active.cursor_x = start.1;
+        self.active.modified = true;
+        self.state_msg = "Línea cortada".to_string();
+    }
+
+    pub fn copy_line(&mut self) {
+        self.clipboard = self.active.buffer.line(self.active.cursor_y);
+        if self.clipboard.is_empty() {
+            self.state_msg = "Línea vacía copiada".to_string();
+        } else {
+            self.state_msg = "Línea copiada".to_string();
+        }
+    }
+
+    pub fn paste_clipboard(&mut self) {
+        if self.clipboard.is_empty() {
+            self.state_msg = "Portapapeles vacío".to_string();
+            return;
+        }
+
+        let clipboard = self.clipboard.clone();
+        self.insert_text(&clipboard);
+    }
+
+    /// Inserta texto (pegado desde la terminal o el portapapeles) en el cursor,
+    /// reemplazando la selección activa
+    pub fn insert_text(&mut self, text: &str) {
+        if !self.ensure_writable() {
+            return;
+        }
+
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        self.edit_over_selection(|editor| {
+            let lines: Vec<&str> = text.split('\n').collect();
+            editor
+                .active
+                .buffer
+                .insert_str(editor.active.cursor_y, editor.active.cursor_x, &text);
+            editor.active.modified = true;
+
+            if lines.len() == 1 {
+                editor.active.cursor_x += lines[0].chars().count();
+            } else {
+                editor.active.cursor_y += lines.len() - 1;
+                editor.active.cursor_x = lines.last().unwrap_or(&"").chars().count();
+            }
+        });
+    }
+
+    pub fn write<W: Write>(&self, stdout: &mut W) {
+        let mut out: Vec<u8> = Vec::with_capacity(16 * 1024);
+
+        write!(out, "{}", cursor::Hide).unwrap();
+        write!(out, "{}", cursor::MoveTo(0, 0)).unwrap();
+        write!(
+            out,
+            "{}",
+            terminal::Clear(terminal::ClearType::FromCursorDown)
+        )
+        .unwrap();
+
+        let visible_lines = self.window_sizes.1.saturating_sub(3) as usize;
+
+        if visible_lines == 0 || self.window_sizes.0 == 0 {
+            ui::render_message(&mut out, 0, "Ventana demasiado pequeña");
+            write!(out, "{}", cursor::Show).unwrap();
+            stdout.write_all(&out).unwrap();
+            stdout.flush().unwrap();
+            return;
+        }
+        let line_num_width = ui::calculate_line_number_width(self.active.buffer.line_count());
+        let language = self.languages.detect(self.active.filename.as_deref());
+
+        let start = self.active.offset_row;
+        let end = (self.active.offset_row + visible_lines).min(self.active.buffer.line_count());
+        let selection = self.selection_range();
+
+        for i in start..end {
+            let line_num = i + 1;
+            let window_row = (i - self.active.offset_row) as u16;
+            let _line_num_digits = self.active.buffer.line_count().to_string().len();
 
             ui::render_line_number(&mut out, line_num, window_row, line_num_width); // valor
             // anterior:
             // line_num_digits
-            let line = self.buffer.line(i);
+            let line = self.active.buffer.line(i);
             ui::render_line_content(
                 &mut out,
                 &line,
                 i,
-                self.offset_col,
-                &self.search,
-                i == self.cursor_y,
+                self.active.offset_col,
+                &self.active.search,
+                ui::LineHighlight {
+                    is_current_line: i == self.active.cursor_y,
+                    selection: selection.and_then(|(sel_start, sel_end)| {
+                        ui::selection_columns(i, sel_start, sel_end)
+                    }),
+                },
                 ui::SyntaxRenderConfig {
                     language,
                     syntax_theme: &self.syntax_theme,
+                    line_state: language
+                        .map(|language| self.active.buffer.syntax_state(i, language))
+                        .unwrap_or_default(),
                 },
             );
         }
 
+        let encoding_label = self.active.buffer.encoding_label();
+        let line_ending_label = self.active.buffer.line_ending_label();
         let status_row = self.window_sizes.1.saturating_sub(3);
         let message_row = self.window_sizes.1.saturating_sub(2);
         let default_row = self.window_sizes.1.saturating_sub(1);
         ui::render_status_bar(
             &mut out,
             status_row,
-            self.filename.as_deref(),
-            self.cursor_y + 1,
-            self.buffer.line_count(),
-            self.cursor_x + 1,
+            &ui::StatusBarInfo {
+                filename: self.active.filename.as_deref(),
+                cursor_line: self.active.cursor_y + 1,
+                total_lines: self.active.buffer.line_count(),
+                cursor_col: self.active.cursor_x + 1,
+                modified: self.active.modified,
+                line_ending: &line_ending_label,
+                encoding: &encoding_label,
+                buffer: (self.active_buffer + 1, self.buffers.len()),
+                read_only: self.active.read_only,
+            },
         );
 
         if self.state_msg != messages::DEFAULT_STATUS {
@@ -400,10 +1540,10 @@ impl Editor {
         ui::render_message(&mut out, default_row, messages::DEFAULT_STATUS);
 
         let (visual_x, visual_y) = ui::calculate_visual_cursor_position(
-            self.cursor_x,
-            self.cursor_y,
-            self.offset_col,
-            self.offset_row,
+            self.active.cursor_x,
+            self.active.cursor_y,
+            self.active.offset_col,
+            self.active.offset_row,
             line_num_width,
         );
         ui::position_cursor(&mut out, visual_x, visual_y);
@@ -415,9 +1555,52 @@ impl Editor {
     }
 }
 
+/// Clave para comparar rutas que pueden referirse al mismo archivo
+fn same_file_key(path: &str) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+/// Ruta libre para guardar la versión del editor junto al archivo (`archivo.local`)
+fn conflict_copy_path(path: &Path) -> PathBuf {
+    let mut candidate = path.as_os_str().to_owned();
+    candidate.push(".local");
+
+    let mut copy = PathBuf::from(&candidate);
+    let mut n = 1;
+    while copy.exists() {
+        let mut numbered = candidate.clone();
+        numbered.push(format!(".{}", n));
+        copy = PathBuf::from(numbered);
+        n += 1;
+    }
+
+    copy
+}
+
+/// Mensaje de error al guardar, distinguiendo los casos más comunes
+fn save_error_message(path: &str, error: &io::Error) -> String {
+    match error.kind() {
+        io::ErrorKind::PermissionDenied => {
+            format!("Sin permiso para escribir '{}'", path)
+        }
+        io::ErrorKind::StorageFull => {
+            format!("No hay espacio en disco para guardar '{}'", path)
+        }
+        io::ErrorKind::ReadOnlyFilesystem => {
+            format!(
+                "No se puede guardar '{}': sistema de archivos de solo lectura",
+                path
+            )
+        }
+        _ => format!("Error al intentar guardar el archivo: {}", error),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Editor;
+    use super::{Editor, save_error_message};
+    use crate::buffer::LineEnding;
+    use std::io;
 
     #[test]
     fn insert_tab_adds_spaces_and_moves_cursor() {
@@ -425,9 +1608,9 @@ mod tests {
 
         editor.insert_tab();
 
-        assert_eq!(editor.buffer.line(0), "    ");
-        assert_eq!(editor.cursor_x, 4);
-        assert_eq!(editor.cursor_y, 0);
+        assert_eq!(editor.active.buffer.line(0), "    ");
+        assert_eq!(editor.active.cursor_x, 4);
+        assert_eq!(editor.active.cursor_y, 0);
     }
 
     #[test]
@@ -439,10 +1622,10 @@ mod tests {
         editor.insert_char('a');
 
         editor.move_to_line_start();
-        assert_eq!(editor.cursor_x, 0);
+        assert_eq!(editor.active.cursor_x, 0);
 
         editor.move_to_line_end();
-        assert_eq!(editor.cursor_x, 4);
+        assert_eq!(editor.active.cursor_x, 4);
     }
 
     #[test]
@@ -456,7 +1639,552 @@ mod tests {
 
         editor.delete_forward_char();
 
-        assert_eq!(editor.buffer.line(0), "ac");
-        assert_eq!(editor.cursor_x, 1);
+        assert_eq!(editor.active.buffer.line(0), "ac");
+        assert_eq!(editor.active.cursor_x, 1);
+    }
+
+    #[test]
+    fn undo_and_redo_restore_text_and_cursor() {
+        let mut editor = Editor::new();
+        editor.insert_char('a');
+        editor.insert_char('b');
+        editor.new_line();
+        editor.insert_char('c');
+
+        editor.undo();
+        assert_eq!(editor.active.buffer.line(1), "");
+        assert_eq!((editor.active.cursor_y, editor.active.cursor_x), (1, 0));
+
+        editor.undo();
+        editor.undo();
+        assert_eq!(editor.active.buffer.line(0), "");
+        assert_eq!((editor.active.cursor_y, editor.active.cursor_x), (0, 0));
+
+        editor.redo();
+        assert_eq!(editor.active.buffer.line(0), "ab");
+        assert_eq!((editor.active.cursor_y, editor.active.cursor_x), (0, 2));
+    }
+
+    #[test]
+    fn undo_delete_forward_keeps_cursor_in_place() {
+        let mut editor = Editor::new();
+        editor.insert_char('a');
+        editor.insert_char('b');
+        editor.move_to_line_start();
+
+        editor.delete_forward_char();
+        editor.undo();
+
+        assert_eq!(editor.active.buffer.line(0), "ab");
+        assert_eq!(editor.active.cursor_x, 0);
+    }
+
+    #[test]
+    fn edits_mark_buffer_as_modified() {
+        let mut editor = Editor::new();
+        assert!(!editor.is_modified());
+
+        editor.insert_char('a');
+        assert!(editor.is_modified());
+    }
+
+    #[test]
+    fn save_file_clears_modified_flag() {
+        let path =
+            std::env::temp_dir().join(format!("hyperion_editor_save_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut editor = Editor::new();
+        editor.insert_char('a');
+
+        assert!(editor.save_file(path));
+        assert!(!editor.is_modified());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn undo_to_unchanged_text_clears_modified() {
+        let mut editor = Editor::new();
+        editor.insert_char('a');
+        editor.insert_char('b');
+
+        editor.undo();
+        assert!(!editor.is_modified());
+
+        editor.redo();
+        assert!(editor.is_modified());
+    }
+
+    #[test]
+    fn undo_to_saved_revision_clears_modified() {
+        let path = std::env::temp_dir().join(format!(
+            "hyperion_editor_undo_saved_{}.txt",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+        let mut editor = Editor::new();
+        editor.insert_char('a');
+        assert!(editor.save_file(path));
+
+        editor.insert_char('b');
+        assert!(editor.is_modified());
+        editor.undo();
+        assert!(!editor.is_modified());
+
+        // Deshacer más allá de lo guardado vuelve a dejarlo modificado
+        editor.undo();
+        assert!(editor.is_modified());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn read_only_buffer_can_be_saved_to_another_path() {
+        let dir = std::env::temp_dir().join(format!("hyperion_save_as_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let original = dir.join("original.txt");
+        let copy = dir.join("copia.txt");
+        std::fs::write(&original, "texto\n").unwrap();
+
+        let mut editor = Editor::new();
+        editor.open_file(original.to_str().unwrap());
+        editor.set_read_only(true);
+
+        assert!(!editor.save_file(original.to_str().unwrap()));
+        assert!(editor.save_file(copy.to_str().unwrap()));
+        assert_eq!(std::fs::read_to_string(&copy).unwrap(), "texto\n");
+        assert_eq!(editor.filename(), copy.to_str());
+        assert!(!editor.is_read_only());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn shift_movement_selects_and_typing_replaces_selection() {
+        let mut editor = Editor::new();
+        for c in "hola".chars() {
+            editor.insert_char(c);
+        }
+        editor.move_to_line_start();
+
+        editor.move_with_selection(Editor::move_right, true);
+        editor.move_with_selection(Editor::move_right, true);
+        assert_eq!(editor.selection_range(), Some(((0, 0), (0, 2))));
+
+        editor.insert_char('X');
+        assert_eq!(editor.active.buffer.line(0), "Xla");
+        assert_eq!(editor.selection_range(), None);
+
+        editor.undo();
+        assert_eq!(editor.active.buffer.line(0), "hola");
+    }
+
+    #[test]
+    fn plain_movement_clears_selection() {
+        let mut editor = Editor::new();
+        editor.insert_char('a');
+        editor.move_with_selection(Editor::move_left, true);
+
+        editor.move_with_selection(Editor::move_right, false);
+
+        assert_eq!(editor.selection_range(), None);
+    }
+
+    #[test]
+    fn cut_line_then_paste_restores_the_buffer() {
+        for line in 0..3 {
+            let mut editor = Editor::new();
+            editor.insert_text("uno\ndos\ntres");
+            let original = editor.active.buffer.text();
+
+            editor.go_to_line((line, 1));
+            editor.cut();
+            assert_eq!(editor.active.buffer.line_count(), 2);
+            editor.paste_clipboard();
+
+            assert_eq!(editor.active.buffer.text(), original, "línea {}", line);
+        }
+    }
+
+    #[test]
+    fn copy_and_cut_use_selection() {
+        let mut editor = Editor::new();
+        for c in "uno dos".chars() {
+            editor.insert_char(c);
+        }
+        editor.move_with_selection(Editor::move_to_line_start, true);
+
+        editor.copy();
+        assert_eq!(editor.clipboard, "uno dos");
+
+        editor.move_with_selection(Editor::move_to_line_end, false);
+        editor.move_with_selection(Editor::move_left, true);
+        editor.move_with_selection(Editor::move_left, true);
+        editor.move_with_selection(Editor::move_left, true);
+        editor.cut();
+
+        assert_eq!(editor.clipboard, "dos");
+        assert_eq!(editor.active.buffer.line(0), "uno ");
+        assert_eq!(editor.active.cursor_x, 4);
+    }
+
+    #[test]
+    fn backspace_deletes_multiline_selection() {
+        let mut editor = Editor::new();
+        editor.insert_char('a');
+        editor.new_line();
+        editor.insert_char('b');
+        editor.move_with_selection(Editor::move_up, true);
+
+        editor.delete_char();
+
+        assert_eq!(editor.active.buffer.line_count(), 1);
+        assert_eq!(editor.active.buffer.line(0), "a");
+    }
+
+    #[test]
+    fn cut_without_selection_removes_current_line() {
+        let mut editor = Editor::new();
+        editor.insert_char('a');
+        editor.new_line();
+        editor.insert_char('b');
+        editor.move_up();
+
+        editor.cut();
+
+        assert_eq!(editor.clipboard, "a\n");
+        assert_eq!(editor.active.buffer.line_count(), 1);
+        assert_eq!(editor.active.buffer.line(0), "b");
+    }
+
+    #[test]
+    fn replace_current_updates_text_and_moves_to_next_match() {
+        let mut editor = Editor::new();
+        for c in "uno dos uno".chars() {
+            editor.insert_char(c);
+        }
+        editor.search("uno");
+
+        assert!(editor.replace_current("1"));
+
+        assert_eq!(editor.active.buffer.line(0), "1 dos uno");
+        assert_eq!(editor.match_count(), 1);
+        assert_eq!(editor.active.search.current_match().unwrap().start_col, 6);
+    }
+
+    #[test]
+    fn replace_matches_is_a_single_undo_step() {
+        let mut editor = Editor::new();
+        for c in "a-b-c".chars() {
+            editor.insert_char(c);
+        }
+        editor.search("re:(\\w)");
+
+        let replaced = editor.replace_matches("[$1]", editor.match_count());
+
+        assert_eq!(replaced, 3);
+        assert_eq!(editor.active.buffer.line(0), "[a]-[b]-[c]");
+
+        editor.undo();
+        assert_eq!(editor.active.buffer.line(0), "a-b-c");
+    }
+
+    #[test]
+    fn replace_all_expands_against_the_original_text() {
+        let mut editor = Editor::new();
+        for c in "xaa".chars() {
+            editor.insert_char(c);
+        }
+        editor.search("re:\\Ba");
+
+        // Tras reemplazar la primera `a`, la segunda queda al inicio de una palabra
+        assert_eq!(editor.replace_matches(" ", editor.match_count()), 2);
+        assert_eq!(editor.active.buffer.line(0), "x  ");
+    }
+
+    #[test]
+    fn replace_one_by_one_expands_against_the_original_text() {
+        let mut editor = Editor::new();
+        for c in "xaaa".chars() {
+            editor.insert_char(c);
+        }
+        editor.search("re:\\B(a)");
+        assert!(editor.prepare_replacement("<$1>"));
+
+        // Como el flujo de Ctrl+R: sí, no, sí
+        assert!(editor.replace_current("<$1>"));
+        editor.next_match();
+        assert!(editor.replace_current("<$1>"));
+
+        assert_eq!(editor.active.buffer.line(0), "x<a>a<a>");
+        assert_eq!(editor.match_count(), 1);
+    }
+
+    #[test]
+    fn search_status_shows_active_options() {
+        let mut editor = Editor::new();
+        for c in "Id id".chars() {
+            editor.insert_char(c);
+        }
+
+        editor.search("i:w:id");
+        editor.next_match();
+
+        assert_eq!(editor.match_count(), 2);
+        assert_eq!(
+            editor.state_msg,
+            "Coincidencia 2/2: 'id' [ignora mayúsculas, palabra completa]"
+        );
+    }
+
+    #[test]
+    fn incremental_search_jumps_to_nearest_match_after_cursor() {
+        let mut editor = Editor::new();
+        for c in "ab ab ab".chars() {
+            editor.insert_char(c);
+        }
+        editor.move_to_line_start();
+        editor.move_right();
+
+        editor.begin_incremental_search();
+        editor.update_incremental_search("a");
+        assert_eq!(editor.active.cursor_x, 3);
+
+        editor.update_incremental_search("ab");
+        assert_eq!(editor.active.cursor_x, 3);
+
+        editor.finish_incremental_search(Some("ab"));
+        assert_eq!(editor.state_msg, "Encontradas 3 coincidencias de 'ab'");
+    }
+
+    #[test]
+    fn cancelled_incremental_search_restores_cursor() {
+        let mut editor = Editor::new();
+        for c in "uno dos".chars() {
+            editor.insert_char(c);
+        }
+        editor.move_to_line_start();
+
+        editor.begin_incremental_search();
+        editor.update_incremental_search("dos");
+        assert_eq!(editor.active.cursor_x, 4);
+
+        editor.finish_incremental_search(None);
+
+        assert_eq!(editor.active.cursor_x, 0);
+        assert!(!editor.active.search.is_active());
+        assert_eq!(
+            editor.state_msg,
+            crate::terminal::messages::SEARCH_CANCELLED
+        );
+    }
+
+    #[test]
+    fn line_ending_command_converts_buffer() {
+        let mut editor = Editor::new();
+
+        editor.execute_command(crate::command::parse_command("crlf").unwrap());
+
+        assert_eq!(editor.active.buffer.line_ending(), LineEnding::Crlf);
+        assert!(editor.is_modified());
+        assert_eq!(editor.state_msg, "Fin de línea: CRLF");
+    }
+
+    #[test]
+    fn encoding_command_changes_save_encoding() {
+        let mut editor = Editor::new();
+
+        editor.execute_command(crate::command::parse_command("codificacion latin1").unwrap());
+
+        assert_eq!(editor.active.buffer.encoding(), encoding_rs::WINDOWS_1252);
+        assert!(editor.is_modified());
+    }
+
+    #[test]
+    fn reopen_requires_a_file() {
+        let mut editor = Editor::new();
+
+        editor.execute_command(crate::command::parse_command("reabrir latin1").unwrap());
+
+        assert_eq!(editor.state_msg, "El buffer no tiene un archivo asociado");
+    }
+
+    #[test]
+    fn save_error_message_by_kind() {
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        let full = io::Error::from(io::ErrorKind::StorageFull);
+        let read_only = io::Error::from(io::ErrorKind::ReadOnlyFilesystem);
+
+        assert_eq!(
+            save_error_message("a.txt", &denied),
+            "Sin permiso para escribir 'a.txt'"
+        );
+        assert!(save_error_message("a.txt", &full).contains("espacio en disco"));
+        assert!(save_error_message("a.txt", &read_only).contains("solo lectura"));
+    }
+
+    #[test]
+    fn pending_recovery_stays_with_its_buffer() {
+        let dir = std::env::temp_dir().join(format!("hyperion_pending_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let with_swap = dir.join("a.txt");
+        let without_swap = dir.join("b.txt");
+        std::fs::write(&with_swap, "a\n").unwrap();
+        std::fs::write(&without_swap, "b\n").unwrap();
+        let swap_file = crate::swap::swap_path(&with_swap);
+        let data = crate::swap::SwapData {
+            cursor: (0, 0),
+            text: "recuperado\n".to_string(),
+        };
+        crate::swap::write(&swap_file, &data).unwrap();
+
+        let mut editor = Editor::new();
+        editor.open_file(with_swap.to_str().unwrap());
+        editor.open_file(without_swap.to_str().unwrap());
+        assert!(!editor.has_pending_recovery());
+
+        editor.switch_buffer(0);
+        assert!(editor.has_pending_recovery());
+        editor.recover_from_swap();
+        assert_eq!(editor.active.buffer.line(0), "recuperado");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn open_file_offers_recovery_from_swap() {
+        let dir = std::env::temp_dir().join(format!("hyperion_swap_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notas.txt");
+        std::fs::write(&path, "original\n").unwrap();
+        let swap_file = crate::swap::swap_path(&path);
+        let data = crate::swap::SwapData {
+            cursor: (0, 3),
+            text: "recuperado\n".to_string(),
+        };
+        crate::swap::write(&swap_file, &data).unwrap();
+
+        let mut editor = Editor::new();
+        editor.open_file(path.to_str().unwrap());
+        assert!(editor.has_pending_recovery());
+        assert_eq!(editor.recovery_diff(), vec!["- original", "+ recuperado"]);
+
+        editor.recover_from_swap();
+        assert_eq!(editor.active.buffer.line(0), "recuperado");
+        assert!(editor.is_modified());
+
+        editor.save_file(path.to_str().unwrap());
+        assert!(!swap_file.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn detects_and_resolves_external_changes() {
+        let dir = std::env::temp_dir().join(format!("hyperion_stamp_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("vigilado.txt");
+        let name = path.to_str().unwrap();
+        std::fs::write(&path, "uno\n").unwrap();
+
+        let mut editor = Editor::new();
+        editor.open_file(name);
+        assert!(!editor.changed_on_disk());
+
+        editor.insert_char('x');
+        std::fs::write(&path, "externo\n").unwrap();
+        assert!(editor.changed_on_disk());
+
+        editor.keep_both_versions();
+        assert_eq!(editor.active.buffer.line(0), "externo");
+        assert!(!editor.is_modified());
+        assert!(!editor.changed_on_disk());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("vigilado.txt.local")).unwrap(),
+            "xuno\n"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn buffers_keep_their_own_state() {
+        let dir = std::env::temp_dir().join(format!("hyperion_buffers_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let first = dir.join("uno.txt");
+        let second = dir.join("dos.txt");
+        std::fs::write(&first, "primero\n").unwrap();
+        std::fs::write(&second, "segundo\n").unwrap();
+
+        let mut editor = Editor::new();
+        editor.open_file(first.to_str().unwrap());
+        editor.insert_char('x');
+        editor.open_file(second.to_str().unwrap());
+
+        assert_eq!(editor.buffer_names().len(), 2);
+        assert_eq!(editor.active.buffer.line(0), "segundo");
+        assert!(!editor.is_modified());
+        assert_eq!(editor.modified_buffers(), vec![0]);
+
+        editor.previous_buffer();
+        assert_eq!(editor.active.buffer.line(0), "xprimero");
+        assert_eq!((editor.active.cursor_y, editor.active.cursor_x), (0, 1));
+        assert!(editor.is_modified());
+
+        editor.open_file(second.to_str().unwrap());
+        assert_eq!(editor.active_buffer(), 1);
+
+        editor.close_buffer();
+        assert_eq!(
+            editor.buffer_names(),
+            vec!["1: ".to_string() + first.to_str().unwrap() + " [+]"]
+        );
+        assert_eq!(editor.active.buffer.line(0), "xprimero");
+
+        editor.close_buffer();
+        assert_eq!(editor.buffer_names(), vec!["1: [Sin nombre]"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn read_only_buffer_rejects_edits() {
+        let mut editor = Editor::new();
+        editor.insert_char('a');
+        editor.execute_command(crate::command::parse_command("solo-lectura").unwrap());
+        assert!(editor.is_read_only());
+
+        editor.insert_char('b');
+        editor.delete_char();
+        editor.undo();
+        editor.execute_command(crate::command::parse_command("crlf").unwrap());
+
+        assert_eq!(editor.active.buffer.line(0), "a");
+        assert_eq!(editor.active.buffer.line_ending(), LineEnding::Lf);
+        assert_eq!(editor.state_msg, crate::terminal::messages::READ_ONLY);
+
+        editor.execute_command(crate::command::parse_command("solo-lectura").unwrap());
+        editor.insert_char('b');
+        assert_eq!(editor.active.buffer.line(0), "ab");
+    }
+
+    #[test]
+    fn insert_text_normalizes_pasted_line_endings() {
+        let mut editor = Editor::new();
+
+        editor.insert_text("uno\r\ndos");
+
+        assert_eq!(editor.active.buffer.line(0), "uno");
+        assert_eq!(editor.active.buffer.line(1), "dos");
+        assert_eq!((editor.active.cursor_y, editor.active.cursor_x), (1, 3));
+    }
+
+    #[test]
+    fn show_search_hit_selects_the_given_match() {
+        let mut editor = Editor::new();
+        editor.insert_text("uno dos\ndos uno\nuno\n");
+
+        editor.show_search_hit("uno", 1, 4);
+
+        assert_eq!((editor.active.cursor_y, editor.active.cursor_x), (1, 4));
+        assert_eq!(editor.match_count(), 3);
+        assert_eq!(editor.active.search.current_index(), Some(1));
     }
 }
@@ -0,0 +1,177 @@
+//! Detección y conversión de la codificación de caracteres de los archivos
+//!
+//! Los archivos se decodifican a UTF-8 al abrirlos y se vuelven a codificar
+//! con su codificación original al guardarlos.
+
+use encoding_rs::{Encoding, REPLACEMENT, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252};
+use std::io;
+
+/// Codificación usada cuando el archivo no tiene BOM y no es UTF-8 válido
+pub const FALLBACK_ENCODING: &Encoding = WINDOWS_1252;
+
+/// Resultado de decodificar el contenido de un archivo
+pub struct Decoded {
+    pub text: String,
+    pub encoding: &'static Encoding,
+    pub has_bom: bool,
+}
+
+/// Decodifica bytes detectando la codificación
+///
+/// Primero se busca un BOM (UTF-8, UTF-16LE o UTF-16BE); si no hay, se usa
+/// UTF-8 cuando el contenido es válido y, si no, `FALLBACK_ENCODING`.
+pub fn decode(bytes: &[u8]) -> Decoded {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return Decoded {
+            text: text.into_owned(),
+            encoding,
+            has_bom: true,
+        };
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Decoded {
+            text: text.to_string(),
+            encoding: UTF_8,
+            has_bom: false,
+        },
+        Err(_) => decode_with(bytes, FALLBACK_ENCODING),
+    }
+}
+
+/// Decodifica bytes con una codificación elegida por el usuario
+pub fn decode_with(bytes: &[u8], encoding: &'static Encoding) -> Decoded {
+    let has_bom =
+        Encoding::for_bom(bytes).is_some_and(|(bom_encoding, _)| bom_encoding == encoding);
+    let (text, _) = encoding.decode_with_bom_removal(bytes);
+
+    Decoded {
+        text: text.into_owned(),
+        encoding,
+        has_bom,
+    }
+}
+
+/// Codifica texto para escribirlo en disco
+///
+/// # Retorna
+/// Los bytes a escribir, o un error si el texto tiene caracteres que no se
+/// pueden representar en la codificación
+pub fn encode(text: &str, encoding: &'static Encoding, has_bom: bool) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() + 3);
+
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        let little_endian = encoding == UTF_16LE;
+        let bom = if has_bom { "\u{FEFF}" } else { "" };
+        for unit in bom.encode_utf16().chain(text.encode_utf16()) {
+            if little_endian {
+                bytes.extend_from_slice(&unit.to_le_bytes());
+            } else {
+                bytes.extend_from_slice(&unit.to_be_bytes());
+            }
+        }
+        return Ok(bytes);
+    }
+
+    if has_bom && encoding == UTF_8 {
+        bytes.extend_from_slice(b"\xEF\xBB\xBF");
+    }
+
+    let (encoded, _, had_errors) = encoding.encode(text);
+    if had_errors {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "el texto contiene caracteres que no se pueden representar en {}",
+                encoding.name()
+            ),
+        ));
+    }
+
+    bytes.extend_from_slice(&encoded);
+    Ok(bytes)
+}
+
+/// Busca una codificación por nombre (`utf-8`, `latin1`, `windows-1252`, `utf-16le`...)
+///
+/// Se rechazan las que no se pueden volver a escribir: `replacement` (a la
+/// que apuntan `iso-2022-kr` o `hz-gb-2312`) decodifica todo como un único
+/// U+FFFD y, al igual que las demás cuya salida es otra codificación,
+/// guardaría el archivo como UTF-8. UTF-16 se codifica aparte en `encode`.
+pub fn lookup(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes()).filter(|&encoding| {
+        encoding == UTF_16LE
+            || encoding == UTF_16BE
+            || (encoding != REPLACEMENT && encoding.output_encoding() == encoding)
+    })
+}
+
+/// Nombre para la barra de estado, indicando si el archivo tiene BOM
+pub fn label(encoding: &'static Encoding, has_bom: bool) -> String {
+    if has_bom {
+        format!("{} BOM", encoding.name())
+    } else {
+        encoding.name().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_utf8_without_bom() {
+        let decoded = decode("año".as_bytes());
+
+        assert_eq!(decoded.text, "año");
+        assert_eq!(decoded.encoding, UTF_8);
+        assert!(!decoded.has_bom);
+    }
+
+    #[test]
+    fn decode_detects_boms() {
+        let decoded = decode(b"\xEF\xBB\xBFhola");
+        assert_eq!(decoded.text, "hola");
+        assert_eq!(decoded.encoding, UTF_8);
+        assert!(decoded.has_bom);
+
+        let decoded = decode(b"\xFF\xFEh\x00i\x00");
+        assert_eq!(decoded.text, "hi");
+        assert_eq!(decoded.encoding, UTF_16LE);
+    }
+
+    #[test]
+    fn decode_falls_back_to_legacy_encoding() {
+        let decoded = decode(b"a\xF1o");
+
+        assert_eq!(decoded.text, "año");
+        assert_eq!(decoded.encoding, WINDOWS_1252);
+    }
+
+    #[test]
+    fn encode_round_trips() {
+        assert_eq!(encode("año", WINDOWS_1252, false).unwrap(), b"a\xF1o");
+        assert_eq!(encode("hi", UTF_8, true).unwrap(), b"\xEF\xBB\xBFhi");
+        assert_eq!(encode("hi", UTF_16LE, true).unwrap(), b"\xFF\xFEh\x00i\x00");
+        assert_eq!(encode("hi", UTF_16BE, false).unwrap(), b"\x00h\x00i");
+    }
+
+    #[test]
+    fn encode_rejects_unmappable_characters() {
+        let error = encode("ルビー", WINDOWS_1252, false).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn lookup_by_label() {
+        assert_eq!(lookup("latin1"), Some(WINDOWS_1252));
+        assert_eq!(lookup("UTF-8"), Some(UTF_8));
+        assert_eq!(lookup("nope"), None);
+        assert_eq!(lookup("utf-16le"), Some(UTF_16LE));
+        assert_eq!(lookup("iso-2022-kr"), None);
+        assert_eq!(lookup("hz-gb-2312"), None);
+        assert_eq!(lookup("csiso2022kr"), None);
+    }
+}
@@ -0,0 +1,299 @@
+//! Escritura segura de archivos
+//!
+//! El contenido se escribe primero en un archivo temporal del mismo
+//! directorio y luego se renombra sobre el original, de modo que un corte
+//! a mitad de la escritura nunca deja el archivo truncado.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs::{self, File, OpenOptions},
+    hash::{Hash, Hasher},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    time::SystemTime,
+};
+
+/// Huella de un archivo en disco, para detectar cambios externos
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+impl FileStamp {
+    /// Lee la fecha de modificación, el tamaño y un hash del contenido
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        let bytes = fs::read(path)?;
+
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+
+        Ok(FileStamp {
+            modified: metadata.modified().ok(),
+            len: bytes.len() as u64,
+            hash: hasher.finish(),
+        })
+    }
+
+    /// Compara solo fecha y tamaño, sin leer el contenido
+    pub fn matches_metadata(&self, path: &Path) -> bool {
+        fs::metadata(path).is_ok_and(|metadata| {
+            metadata.len() == self.len && metadata.modified().ok() == self.modified
+        })
+    }
+
+    /// Indica si el contenido es el mismo, aunque la fecha haya cambiado
+    pub fn same_content(&self, other: &FileStamp) -> bool {
+        self.len == other.len && self.hash == other.hash
+    }
+}
+
+/// Escribe `bytes` en `path` de forma atómica
+///
+/// # Argumentos
+/// * `path` - Archivo de destino; si es un enlace simbólico se escribe en su destino
+/// * `bytes` - Contenido completo del archivo
+/// * `backup` - Si es `true`, conserva la versión anterior como `archivo~`
+///
+/// Se conservan los permisos y, cuando es posible, el dueño del archivo original.
+pub fn write_atomic(path: &Path, bytes: &[u8], backup: bool) -> io::Result<()> {
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let original = fs::metadata(&target).ok();
+
+    if original.is_some() {
+        // Falla con PermissionDenied si el archivo no es escribible, aunque
+        // el directorio sí lo sea y el renombrado fuera posible
+        OpenOptions::new().append(true).open(&target)?;
+    }
+
+    let temp_path = temp_path_for(&target);
+    if let Err(e) = write_temp_file(&temp_path, bytes, original.as_ref()) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    if backup
+        && original.is_some()
+        && let Err(e) = fs::copy(&target, backup_path(&target))
+    {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    if let Err(e) = fs::rename(&temp_path, &target) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    sync_parent_dir(&target);
+    Ok(())
+}
+
+/// Indica si el usuario actual puede escribir en el archivo
+pub fn is_writable(path: &Path) -> bool {
+    OpenOptions::new().append(true).open(path).is_ok()
+}
+
+/// Ruta de la copia de respaldo de un archivo (`archivo~`)
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push("~");
+    PathBuf::from(name)
+}
+
+fn temp_path_for(target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    target.with_file_name(format!(".{}.hyperion-tmp-{}", name, process::id()))
+}
+
+/// Crea el temporal con los permisos y el dueño del original antes de
+/// escribir, para que el contenido nunca quede legible con permisos más
+/// abiertos que los del archivo que reemplaza
+fn write_temp_file(
+    temp_path: &Path,
+    bytes: &[u8],
+    original: Option<&fs::Metadata>,
+) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    if let Some(metadata) = original {
+        restrict_mode(&mut options, metadata);
+    }
+    let mut file = options.open(temp_path)?;
+
+    if let Some(metadata) = original {
+        // El dueño va primero: `chown` puede quitar los bits setuid/setgid
+        copy_ownership(&file, metadata);
+        file.set_permissions(metadata.permissions())?;
+    }
+
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Crea el archivo directamente con el modo del original (menos la umask)
+#[cfg(unix)]
+fn restrict_mode(options: &mut OpenOptions, metadata: &fs::Metadata) {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    options.mode(metadata.permissions().mode() & 0o777);
+}
+
+#[cfg(not(unix))]
+fn restrict_mode(_options: &mut OpenOptions, _metadata: &fs::Metadata) {}
+
+/// Intenta conservar dueño y grupo; sin privilegios solo se puede cambiar el
+/// grupo, así que los errores se ignoran
+#[cfg(unix)]
+fn copy_ownership(file: &File, metadata: &fs::Metadata) {
+    use std::os::unix::fs::{MetadataExt, fchown};
+
+    if fchown(file, Some(metadata.uid()), Some(metadata.gid())).is_err() {
+        let _ = fchown(file, None, Some(metadata.gid()));
+    }
+}
+
+#[cfg(not(unix))]
+fn copy_ownership(_file: &File, _metadata: &fs::Metadata) {}
+
+/// Sincroniza el directorio para que el renombrado sobreviva a un corte de luz
+fn sync_parent_dir(target: &Path) {
+    let dir = match target.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        env,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    fn unique_temp_dir() -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = env::temp_dir().join(format!("hyperion_file_io_{nanos}"));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn write_atomic_creates_and_replaces_file() {
+        let dir = unique_temp_dir();
+        let path = dir.join("nuevo.txt");
+
+        write_atomic(&path, b"uno", false).unwrap();
+        write_atomic(&path, b"dos", false).unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"dos");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn file_stamp_detects_content_changes() {
+        let dir = unique_temp_dir();
+        let path = dir.join("vigilado.txt");
+        fs::write(&path, "uno").unwrap();
+        let stamp = FileStamp::read(&path).unwrap();
+        assert!(stamp.matches_metadata(&path));
+
+        fs::write(&path, "uno").unwrap();
+        assert!(stamp.same_content(&FileStamp::read(&path).unwrap()));
+
+        fs::write(&path, "otro").unwrap();
+        assert!(!stamp.matches_metadata(&path));
+        assert!(!stamp.same_content(&FileStamp::read(&path).unwrap()));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn write_atomic_keeps_backup() {
+        let dir = unique_temp_dir();
+        let path = dir.join("datos.txt");
+        fs::write(&path, "viejo").unwrap();
+
+        write_atomic(&path, b"nuevo", true).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "nuevo");
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "viejo");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_atomic_preserves_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = unique_temp_dir();
+        let path = dir.join("script.sh");
+        fs::write(&path, "echo hola").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o750)).unwrap();
+
+        write_atomic(&path, b"echo chau", false).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn temp_file_is_created_with_original_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = unique_temp_dir();
+        let path = dir.join("secreto.txt");
+        fs::write(&path, "clave").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        restrict_mode(&mut options, &metadata);
+        let temp_path = temp_path_for(&path);
+        drop(options.open(&temp_path).unwrap());
+
+        let mode = fs::metadata(&temp_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o077, 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_atomic_follows_symlinks() {
+        let dir = unique_temp_dir();
+        let target = dir.join("real.txt");
+        let link = dir.join("enlace.txt");
+        fs::write(&target, "a").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        write_atomic(&link, b"b", false).unwrap();
+
+        assert!(
+            fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(fs::read_to_string(&target).unwrap(), "b");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,466 @@
+//! Buscador difuso de archivos del proyecto
+//!
+//! Recorre el directorio actual respetando los `.gitignore` (y omitiendo
+//! siempre `.git/` y `target/`), ordena las rutas según qué tan bien
+//! coinciden con lo escrito y genera la vista previa del archivo elegido.
+
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::Path,
+};
+
+use regex::Regex;
+
+use crate::ui;
+
+/// Cantidad máxima de archivos que se listan, para no bloquear en árboles enormes
+const MAX_FILES: usize = 50_000;
+
+/// Bytes que se leen de un archivo para la vista previa
+const PREVIEW_BYTES: u64 = 64 * 1024;
+
+/// Directorios que nunca se recorren
+const SKIPPED_DIRS: [&str; 2] = [".git", "target"];
+
+/// Regla de un `.gitignore`
+#[derive(Debug)]
+struct IgnoreRule {
+    /// Directorio del `.gitignore`, relativo a la raíz (vacío en la raíz)
+    base: String,
+
+    /// Patrón traducido a expresión regular sobre la ruta relativa a `base`
+    regex: Regex,
+
+    /// La regla empieza con `!` y vuelve a incluir lo que coincide
+    negated: bool,
+
+    /// La regla termina con `/` y solo aplica a directorios
+    dir_only: bool,
+}
+
+impl IgnoreRule {
+    /// Interpreta una línea de un `.gitignore`
+    fn parse(line: &str, base: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+
+        // Un patrón con `/` se ancla al directorio del `.gitignore`; si no,
+        // coincide con el nombre en cualquier nivel
+        let anchored = pattern.contains('/');
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+        if pattern.is_empty() {
+            return None;
+        }
+
+        let prefix = if anchored { "^" } else { "^(?:.*/)?" };
+        let regex = Regex::new(&format!("{}{}$", prefix, glob_to_regex(pattern))).ok()?;
+
+        Some(IgnoreRule {
+            base: base.to_string(),
+            regex,
+            negated,
+            dir_only,
+        })
+    }
+
+    /// Indica si la regla aplica a `path` (relativa a la raíz del proyecto)
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        let relative = if self.base.is_empty() {
+            path
+        } else {
+            match path
+                .strip_prefix(self.base.as_str())
+                .and_then(|rest| rest.strip_prefix('/'))
+            {
+                Some(rest) => rest,
+                None => return false,
+            }
+        };
+
+        self.regex.is_match(relative)
+    }
+}
+
+/// Traduce un patrón glob de `.gitignore` a expresión regular
+///
+/// `*` y `?` no cruzan directorios; `**` sí.
+pub(crate) fn glob_to_regex(pattern: &str) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut regex = String::new();
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        match chars[idx] {
+            '*' if chars.get(idx + 1) == Some(&'*') => {
+                if chars.get(idx + 2) == Some(&'/') {
+                    regex.push_str("(?:.*/)?");
+                    idx += 3;
+                } else {
+                    regex.push_str(".*");
+                    idx += 2;
+                }
+                continue;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => match chars[idx..].iter().position(|&c| c == ']') {
+                Some(len) if len > 1 => {
+                    let class: String = chars[idx + 1..idx + len].iter().collect();
+                    let class = class
+                        .strip_prefix('!')
+                        .map_or(class.clone(), |rest| format!("^{}", rest));
+                    regex.push('[');
+                    regex.push_str(&class.replace('\\', "\\\\"));
+                    regex.push(']');
+                    idx += len + 1;
+                    continue;
+                }
+                _ => regex.push_str("\\["),
+            },
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        idx += 1;
+    }
+
+    regex
+}
+
+/// Indica si `path` queda excluida por las reglas; la última que coincide decide
+fn is_ignored(rules: &[IgnoreRule], path: &str, is_dir: bool) -> bool {
+    rules
+        .iter()
+        .rev()
+        .find(|rule| rule.matches(path, is_dir))
+        .is_some_and(|rule| !rule.negated)
+}
+
+/// Lista los archivos de `root`, con rutas relativas separadas por `/`
+///
+/// # Argumentos
+/// * `root` - Directorio raíz del proyecto
+///
+/// # Retorna
+/// Las rutas ordenadas alfabéticamente (como máximo `MAX_FILES`)
+pub fn project_files(root: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut rules = Vec::new();
+    walk(root, "", &mut rules, &mut files);
+    files.sort();
+    files
+}
+
+fn walk(dir: &Path, relative: &str, rules: &mut Vec<IgnoreRule>, files: &mut Vec<String>) {
+    let rules_before = rules.len();
+    if let Ok(content) = fs::read_to_string(dir.join(".gitignore")) {
+        rules.extend(
+            content
+                .lines()
+                .filter_map(|line| IgnoreRule::parse(line, relative)),
+        );
+    }
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        rules.truncate(rules_before);
+        return;
+    };
+
+    let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        if files.len() >= MAX_FILES {
+            break;
+        }
+
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        // `file_type` no sigue enlaces simbólicos, así se evitan ciclos
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = if relative.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", relative, name)
+        };
+
+        if file_type.is_dir() {
+            if SKIPPED_DIRS.contains(&name.as_str()) || is_ignored(rules, &path, true) {
+                continue;
+            }
+            walk(&entry.path(), &path, rules, files);
+        } else if !is_ignored(rules, &path, false) {
+            files.push(path);
+        }
+    }
+
+    rules.truncate(rules_before);
+}
+
+/// Puntaje de coincidencia difusa de `query` en `candidate`
+///
+/// Todas las letras de la consulta deben aparecer en orden (sin distinguir
+/// mayúsculas). Se premian las letras consecutivas, las que empiezan una
+/// palabra o un componente de la ruta y las coincidencias en el nombre del
+/// archivo; se penalizan los huecos.
+///
+/// # Retorna
+/// El puntaje (mayor es mejor), o `None` si no coincide
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let chars: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    // Primer final posible de la coincidencia...
+    let mut qi = 0;
+    let mut end = 0;
+    for (idx, &c) in lower.iter().enumerate() {
+        if c == query[qi] {
+            qi += 1;
+            if qi == query.len() {
+                end = idx;
+                break;
+            }
+        }
+    }
+    if qi < query.len() {
+        return None;
+    }
+
+    // ...y desde ahí hacia atrás, el inicio más cercano (ventana más corta)
+    let mut qi = query.len();
+    let mut start = end;
+    for idx in (0..=end).rev() {
+        if lower[idx] == query[qi - 1] {
+            qi -= 1;
+            if qi == 0 {
+                start = idx;
+                break;
+            }
+        }
+    }
+
+    let name_start = candidate
+        .rfind('/')
+        .map_or(0, |idx| candidate[..idx].chars().count() + 1);
+    let mut score = 0;
+    let mut qi = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for (idx, &c) in lower.iter().enumerate().take(end + 1).skip(start) {
+        if qi == query.len() || c != query[qi] {
+            continue;
+        }
+
+        score += 1;
+        if is_word_start(&chars, idx) {
+            score += 8;
+        }
+        if idx >= name_start {
+            score += 2;
+        }
+        match previous_match {
+            Some(prev) if prev + 1 == idx => score += 5,
+            Some(prev) => score -= (idx - prev - 1).min(10) as i64,
+            None => {}
+        }
+
+        previous_match = Some(idx);
+        qi += 1;
+    }
+
+    Some(score)
+}
+
+/// Indica si el carácter en `idx` empieza una palabra o un componente de la ruta
+fn is_word_start(chars: &[char], idx: usize) -> bool {
+    let Some(previous) = idx.checked_sub(1).map(|i| chars[i]) else {
+        return true;
+    };
+
+    matches!(previous, '/' | '_' | '-' | '.' | ' ')
+        || (previous.is_lowercase() && chars[idx].is_uppercase())
+}
+
+/// Filtra y ordena `items` según `query`
+///
+/// # Retorna
+/// Los índices de los elementos que coinciden, del mejor al peor; con una
+/// consulta vacía se conserva el orden original
+pub fn rank(query: &str, items: &[String]) -> Vec<usize> {
+    if query.is_empty() {
+        return (0..items.len()).collect();
+    }
+
+    let mut scored: Vec<(i64, usize)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, item)| fuzzy_score(query, item).map(|score| (score, idx)))
+        .collect();
+
+    // A igual puntaje, primero las rutas más cortas
+    scored.sort_by(|(a_score, a_idx), (b_score, b_idx)| {
+        b_score
+            .cmp(a_score)
+            .then(items[*a_idx].len().cmp(&items[*b_idx].len()))
+            .then(a_idx.cmp(b_idx))
+    });
+    scored.into_iter().map(|(_, idx)| idx).collect()
+}
+
+/// Primeras líneas de un archivo para la vista previa
+///
+/// # Argumentos
+/// * `path` - Archivo a mostrar
+/// * `max_lines` - Cantidad máxima de líneas
+pub fn preview(path: &Path, max_lines: usize) -> Vec<String> {
+    let mut bytes = Vec::new();
+    let result = File::open(path).and_then(|file| file.take(PREVIEW_BYTES).read_to_end(&mut bytes));
+    if let Err(e) = result {
+        return vec![format!("(no se pudo leer el archivo: {})", e)];
+    }
+
+    if bytes.contains(&0) {
+        return vec!["(archivo binario)".to_string()];
+    }
+
+    String::from_utf8_lossy(&bytes)
+        .lines()
+        .take(max_lines)
+        .map(|line| ui::replace_control_chars(&line.replace('\t', "    ")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(line: &str) -> IgnoreRule {
+        IgnoreRule::parse(line, "").unwrap()
+    }
+
+    #[test]
+    fn gitignore_patterns_follow_git_semantics() {
+        assert!(rule("*.log").matches("logs/debug.log", false));
+        assert!(!rule("*.log").matches("debug.log.txt", false));
+        assert!(rule("/build").matches("build", true));
+        assert!(!rule("/build").matches("src/build", true));
+        assert!(rule("docs/*.md").matches("docs/intro.md", false));
+        assert!(!rule("docs/*.md").matches("docs/api/intro.md", false));
+        assert!(rule("**/cache").matches("a/b/cache", true));
+        assert!(rule("out/").matches("out", true));
+        assert!(!rule("out/").matches("out", false));
+        assert!(rule("file[0-9].txt").matches("file3.txt", false));
+        assert!(IgnoreRule::parse("# comentario", "").is_none());
+    }
+
+    #[test]
+    fn nested_rules_apply_below_their_directory() {
+        let rule = IgnoreRule::parse("*.tmp", "src").unwrap();
+
+        assert!(rule.matches("src/a.tmp", false));
+        assert!(rule.matches("src/deep/a.tmp", false));
+        assert!(!rule.matches("a.tmp", false));
+    }
+
+    #[test]
+    fn negated_rules_reinclude_files() {
+        let rules = vec![rule("*.log"), rule("!keep.log")];
+
+        assert!(is_ignored(&rules, "debug.log", false));
+        assert!(!is_ignored(&rules, "keep.log", false));
+        assert!(!is_ignored(&rules, "main.rs", false));
+    }
+
+    #[test]
+    fn project_files_respects_gitignore_and_skips_target() {
+        let root = std::env::temp_dir().join(format!("hyperion_finder_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for dir in ["src", "target/debug", ".git", "node_modules/lib", "src/gen"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join(".gitignore"), "node_modules/\n*.log\n").unwrap();
+        fs::write(root.join("src/.gitignore"), "gen/\n").unwrap();
+        for file in [
+            "README.md",
+            "debug.log",
+            "src/main.rs",
+            "src/gen/out.rs",
+            "target/debug/hy",
+            ".git/HEAD",
+            "node_modules/lib/index.js",
+        ] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        assert_eq!(
+            project_files(&root),
+            [".gitignore", "README.md", "src/.gitignore", "src/main.rs"]
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn fuzzy_score_requires_all_letters_in_order() {
+        assert!(fuzzy_score("edt", "src/editor.rs").is_some());
+        assert!(fuzzy_score("EDITOR", "src/editor.rs").is_some());
+        assert!(fuzzy_score("tde", "src/editor.rs").is_none());
+        assert_eq!(fuzzy_score("", "src/editor.rs"), Some(0));
+    }
+
+    #[test]
+    fn rank_prefers_file_names_and_word_starts() {
+        let items = vec![
+            "docs/main_reference.txt".to_string(),
+            "src/main.rs".to_string(),
+            "examples/domain.rs".to_string(),
+            "src/ui.rs".to_string(),
+        ];
+
+        let ranked = rank("main", &items);
+
+        assert_eq!(ranked[0], 1);
+        assert!(!ranked.contains(&3));
+        assert_eq!(rank("", &items), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn preview_reads_first_lines_and_detects_binaries() {
+        let dir = std::env::temp_dir();
+        let text = dir.join(format!("hyperion_preview_{}.txt", std::process::id()));
+        let binary = dir.join(format!("hyperion_preview_{}.bin", std::process::id()));
+        fs::write(&text, "uno\r\n\tdos\x1b[2J\r\ntres\n").unwrap();
+        fs::write(&binary, [0u8, 1, 2]).unwrap();
+
+        assert_eq!(preview(&text, 2), ["uno", "    dos\u{FFFD}[2J"]);
+        assert_eq!(preview(&binary, 2), ["(archivo binario)"]);
+
+        fs::remove_file(text).unwrap();
+        fs::remove_file(binary).unwrap();
+    }
+}
@@ -0,0 +1,250 @@
+//! Búsqueda en todos los archivos del proyecto
+//!
+//! Recorre los mismos archivos que el buscador (`finder::project_files`),
+//! omite los binarios y busca en cada uno con `SearchState`, así la consulta
+//! acepta los mismos prefijos (`re:`, `i:`, `s:`, `w:`) que `Ctrl+F`.
+
+use std::{fs, path::Path};
+
+use encoding_rs::Encoding;
+
+use crate::{
+    encoding, finder,
+    search::{self, SearchState},
+    ui,
+};
+
+/// Cantidad máxima de resultados
+const MAX_HITS: usize = 10_000;
+
+/// Los archivos más grandes se omiten
+const MAX_FILE_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Bytes iniciales que se revisan para detectar archivos binarios
+const BINARY_CHECK_BYTES: usize = 8 * 1024;
+
+/// Caracteres de contexto que se guardan a cada lado de una coincidencia
+const EXCERPT_CONTEXT: usize = 80;
+
+/// Coincidencia en un archivo del proyecto
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrepHit {
+    /// Ruta relativa a la raíz del proyecto
+    pub path: String,
+
+    /// Línea de la coincidencia (desde 0)
+    pub line: usize,
+
+    /// Columna de la coincidencia, en caracteres (desde 0)
+    pub column: usize,
+
+    /// Fragmento de la línea alrededor de la coincidencia
+    ///
+    /// No se guarda la línea entera: en un archivo minificado una sola línea
+    /// puede tener megas y repetirse en miles de resultados.
+    pub text: String,
+}
+
+impl GrepHit {
+    /// Texto para la lista de resultados: `ruta:línea:columna: texto`
+    ///
+    /// Los caracteres de control del archivo se muestran como `�`.
+    pub fn label(&self) -> String {
+        let label = format!(
+            "{}:{}:{}: {}",
+            self.path,
+            self.line + 1,
+            self.column + 1,
+            self.text.trim().replace('\t', " ")
+        );
+        ui::replace_control_chars(&label)
+    }
+}
+
+/// Resultado de una búsqueda en el proyecto
+#[derive(Debug, Default)]
+pub struct GrepResults {
+    pub hits: Vec<GrepHit>,
+
+    /// Se alcanzó `MAX_HITS` y no se siguió buscando
+    pub truncated: bool,
+}
+
+/// Busca `input` en los archivos de texto de `root`
+///
+/// # Argumentos
+/// * `root` - Directorio raíz del proyecto
+/// * `input` - Consulta, con los mismos prefijos que la búsqueda del buffer
+///
+/// # Retorna
+/// Las coincidencias en orden de archivo y línea, o un mensaje de error si
+/// la consulta está vacía o la expresión regular es inválida
+pub fn search_project(root: &Path, input: &str) -> Result<GrepResults, String> {
+    let (options, query) = search::parse_query(input);
+    if query.is_empty() {
+        return Err("Indique el texto a buscar".to_string());
+    }
+
+    let mut state = SearchState::new();
+    state.set_options(options);
+    let mut results = GrepResults::default();
+
+    for path in finder::project_files(root) {
+        let Some(lines) = read_text_lines(&root.join(&path)) else {
+            continue;
+        };
+
+        state.search(query, &lines);
+        if let Some(error) = state.error() {
+            return Err(format!("Expresión regular inválida: {}", error));
+        }
+
+        for m in state.matches() {
+            if results.hits.len() >= MAX_HITS {
+                results.truncated = true;
+                return Ok(results);
+            }
+
+            results.hits.push(GrepHit {
+                path: path.clone(),
+                line: m.line,
+                column: m.start_col,
+                text: excerpt(&lines[m.line], m.start_col, m.end_col),
+            });
+        }
+    }
+
+    Ok(results)
+}
+
+/// Parte de `line` entre `start_col` y `end_col` más `EXCERPT_CONTEXT`
+/// caracteres a cada lado, con `…` donde se recortó
+fn excerpt(line: &str, start_col: usize, end_col: usize) -> String {
+    let first = start_col.saturating_sub(EXCERPT_CONTEXT);
+    let last = end_col + EXCERPT_CONTEXT;
+
+    let mut text = String::new();
+    if first > 0 {
+        text.push('…');
+    }
+    text.extend(line.chars().skip(first).take(last - first));
+    if line.chars().nth(last).is_some() {
+        text.push('…');
+    }
+    text
+}
+
+/// Lee las líneas de un archivo de texto, o `None` si es binario o muy grande
+fn read_text_lines(path: &Path) -> Option<Vec<String>> {
+    if fs::metadata(path).ok()?.len() > MAX_FILE_SIZE {
+        return None;
+    }
+
+    let bytes = fs::read(path).ok()?;
+    if is_binary(&bytes) {
+        return None;
+    }
+
+    let decoded = encoding::decode(&bytes);
+    Some(decoded.text.lines().map(str::to_string).collect())
+}
+
+/// Un byte nulo al inicio indica un archivo binario, salvo en UTF-16 con BOM
+fn is_binary(bytes: &[u8]) -> bool {
+    Encoding::for_bom(bytes).is_none() && bytes[..bytes.len().min(BINARY_CHECK_BYTES)].contains(&0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_project(name: &str) -> std::path::PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("hyperion_grep_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(
+            root.join("src/main.rs"),
+            "fn main() {\n\tlet total = 1;\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn total() {}\n").unwrap();
+        fs::write(root.join("target/out.rs"), "total\n").unwrap();
+        fs::write(root.join("data.bin"), b"total\0\x01").unwrap();
+        root
+    }
+
+    #[test]
+    fn finds_hits_in_text_files_only() {
+        let root = temp_project("hits");
+
+        let results = search_project(&root, "total").unwrap();
+        let labels: Vec<String> = results.hits.iter().map(GrepHit::label).collect();
+
+        assert_eq!(
+            labels,
+            [
+                "src/lib.rs:1:8: pub fn total() {}",
+                "src/main.rs:2:6: let total = 1;"
+            ]
+        );
+        assert!(!results.truncated);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn hits_in_long_lines_keep_only_an_excerpt() {
+        let root = temp_project("long");
+        let line = format!("{}total{}", "a".repeat(5_000), "b".repeat(5_000));
+        fs::write(root.join("bundle.js"), &line).unwrap();
+
+        let results = search_project(&root, "total").unwrap();
+        let hit = results
+            .hits
+            .iter()
+            .find(|hit| hit.path == "bundle.js")
+            .unwrap();
+
+        assert_eq!(hit.column, 5_000);
+        assert_eq!(
+            hit.text,
+            format!("…{}total{}…", "a".repeat(80), "b".repeat(80))
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn accepts_search_prefixes_and_reports_errors() {
+        let root = temp_project("prefixes");
+
+        let results = search_project(&root, "re:fn \\w+").unwrap();
+        assert_eq!(results.hits.len(), 2);
+        assert_eq!(search_project(&root, "i:TOTAL").unwrap().hits.len(), 2);
+        assert!(search_project(&root, "re:(").is_err());
+        assert!(search_project(&root, "").is_err());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn label_hides_control_characters() {
+        let hit = GrepHit {
+            path: "a.txt".to_string(),
+            line: 0,
+            column: 2,
+            text: "\tx = 1;\r\x1b[31m".to_string(),
+        };
+
+        assert_eq!(hit.label(), "a.txt:1:3: x = 1;\u{FFFD}\u{FFFD}[31m");
+    }
+
+    #[test]
+    fn utf16_files_with_bom_are_not_binary() {
+        assert!(!is_binary(&[0xFF, 0xFE, b'a', 0]));
+        assert!(is_binary(b"a\0b"));
+        assert!(!is_binary(b"texto"));
+    }
+}
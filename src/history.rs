@@ -0,0 +1,242 @@
+//! Historial de los prompts
+//!
+//! Cada tipo de prompt (búsqueda, ir a línea, archivos, comandos...) tiene su
+//! propio historial, que se recorre con las flechas arriba/abajo. Si está
+//! habilitado en la configuración, se guarda en
+//! `$XDG_DATA_HOME/hyperion/history` para conservarlo entre sesiones.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::file_io;
+
+/// Cantidad máxima de entradas que se conservan por historial
+const MAX_ENTRIES: usize = 100;
+
+/// Tipo de prompt al que pertenece un historial
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryKind {
+    /// Buscar (`Ctrl+F`) y el texto a buscar de `Ctrl+R`
+    Search,
+
+    /// Texto de reemplazo de `Ctrl+R`
+    Replace,
+
+    /// Ir a línea (`Ctrl+G`)
+    Goto,
+
+    /// Abrir y guardar como
+    File,
+
+    /// Prompt de comandos (`Ctrl+E`)
+    Command,
+}
+
+impl HistoryKind {
+    const ALL: [HistoryKind; 5] = [
+        HistoryKind::Search,
+        HistoryKind::Replace,
+        HistoryKind::Goto,
+        HistoryKind::File,
+        HistoryKind::Command,
+    ];
+
+    /// Nombre usado en el archivo de historial
+    fn key(self) -> &'static str {
+        match self {
+            HistoryKind::Search => "search",
+            HistoryKind::Replace => "replace",
+            HistoryKind::Goto => "goto",
+            HistoryKind::File => "file",
+            HistoryKind::Command => "command",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.key() == key)
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&kind| kind == self).unwrap_or(0)
+    }
+}
+
+/// Entradas de un prompt, de la más vieja a la más reciente
+#[derive(Clone, Debug, Default)]
+pub struct PromptHistory {
+    entries: Vec<String>,
+}
+
+impl PromptHistory {
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Agrega una entrada como la más reciente, quitando repeticiones anteriores
+    pub fn push(&mut self, entry: &str) {
+        if entry.trim().is_empty() {
+            return;
+        }
+
+        self.entries.retain(|existing| existing != entry);
+        self.entries.push(entry.to_string());
+
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+    }
+}
+
+/// Historiales de todos los prompts
+#[derive(Debug, Default)]
+pub struct Histories {
+    histories: [PromptHistory; HistoryKind::ALL.len()],
+
+    /// Archivo donde se guardan; `None` si no se persisten
+    path: Option<PathBuf>,
+}
+
+impl Histories {
+    /// Historiales que solo duran mientras el editor está abierto
+    pub fn in_memory() -> Self {
+        Histories::default()
+    }
+
+    /// Carga los historiales de `path`; si el archivo no existe empiezan vacíos
+    pub fn load(path: PathBuf) -> Self {
+        let mut histories = fs::read_to_string(&path)
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default();
+        histories.path = Some(path);
+        histories
+    }
+
+    pub fn get_mut(&mut self, kind: HistoryKind) -> &mut PromptHistory {
+        &mut self.histories[kind.index()]
+    }
+
+    /// Guarda los historiales si se cargaron desde un archivo
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        file_io::write_atomic(path, self.serialize().as_bytes(), false)
+    }
+
+    /// Formato del archivo: una entrada por línea, `tipo<TAB>texto`
+    fn serialize(&self) -> String {
+        let mut content = String::new();
+        for kind in HistoryKind::ALL {
+            for entry in self.histories[kind.index()].entries() {
+                content.push_str(kind.key());
+                content.push('\t');
+                content.push_str(entry);
+                content.push('\n');
+            }
+        }
+        content
+    }
+
+    fn parse(content: &str) -> Self {
+        let mut histories = Histories::default();
+        for line in content.lines() {
+            let Some((key, entry)) = line.split_once('\t') else {
+                continue;
+            };
+            if let Some(kind) = HistoryKind::from_key(key) {
+                histories.get_mut(kind).push(entry);
+            }
+        }
+        histories
+    }
+}
+
+/// Ruta del archivo de historial según XDG (`~/.local/share/hyperion/history`)
+pub fn history_file_path() -> Option<PathBuf> {
+    let xdg_data_home = env::var_os("XDG_DATA_HOME").map(PathBuf::from);
+    let home_dir = env::var_os("HOME").map(PathBuf::from);
+
+    history_file_path_with(xdg_data_home, home_dir.as_deref())
+}
+
+fn history_file_path_with(
+    xdg_data_home: Option<PathBuf>,
+    home_dir: Option<&Path>,
+) -> Option<PathBuf> {
+    let data_dir = xdg_data_home
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| home_dir.map(|home| home.join(".local/share")))?;
+
+    Some(data_dir.join("hyperion/history"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_moves_repeated_entries_to_the_end() {
+        let mut history = PromptHistory::default();
+        history.push("uno");
+        history.push("dos");
+        history.push("uno");
+        history.push("   ");
+
+        assert_eq!(history.entries(), ["dos", "uno"]);
+    }
+
+    #[test]
+    fn push_keeps_at_most_max_entries() {
+        let mut history = PromptHistory::default();
+        for i in 0..MAX_ENTRIES + 5 {
+            history.push(&i.to_string());
+        }
+
+        assert_eq!(history.entries().len(), MAX_ENTRIES);
+        assert_eq!(history.entries()[0], "5");
+    }
+
+    #[test]
+    fn serialize_round_trips_per_kind() {
+        let mut histories = Histories::in_memory();
+        histories.get_mut(HistoryKind::Search).push("re:fn \\w+");
+        histories.get_mut(HistoryKind::Goto).push("10, 2");
+
+        let parsed = Histories::parse(&histories.serialize());
+
+        assert_eq!(
+            parsed.histories[HistoryKind::Search.index()].entries(),
+            ["re:fn \\w+"]
+        );
+        assert_eq!(
+            parsed.histories[HistoryKind::Goto.index()].entries(),
+            ["10, 2"]
+        );
+        assert!(
+            parsed.histories[HistoryKind::File.index()]
+                .entries()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn history_path_prefers_xdg_data_home() {
+        let home = PathBuf::from("/home/ana");
+
+        assert_eq!(
+            history_file_path_with(Some(PathBuf::from("/datos")), Some(&home)),
+            Some(PathBuf::from("/datos/hyperion/history"))
+        );
+        assert_eq!(
+            history_file_path_with(None, Some(&home)),
+            Some(PathBuf::from("/home/ana/.local/share/hyperion/history"))
+        );
+        assert_eq!(history_file_path_with(None, None), None);
+    }
+}
@@ -0,0 +1,789 @@
+//! Definiciones declarativas de lenguajes para el resaltado de sintaxis
+//!
+//! Cada lenguaje se describe con un archivo TOML: extensiones y patrones de
+//! nombre de archivo, marcadores de comentarios, delimitadores de strings,
+//! palabras clave y formato de los números. Los lenguajes más comunes vienen
+//! incluidos (ver `languages/*.toml`) y se pueden agregar o reemplazar
+//! lenguajes desde secciones `[languages.<nombre>]` del archivo de
+//! configuración o con archivos en `~/.config/hyperion/languages/*.toml`.
+
+use std::{fs, path::Path};
+
+use regex::Regex;
+
+use crate::{config, finder};
+
+/// Definiciones incluidas en el editor
+const BUILTIN: [(&str, &str); 15] = [
+    ("rust", include_str!("../languages/rust.toml")),
+    ("python", include_str!("../languages/python.toml")),
+    ("javascript", include_str!("../languages/javascript.toml")),
+    ("typescript", include_str!("../languages/typescript.toml")),
+    ("c", include_str!("../languages/c.toml")),
+    ("cpp", include_str!("../languages/cpp.toml")),
+    ("go", include_str!("../languages/go.toml")),
+    ("shell", include_str!("../languages/shell.toml")),
+    ("toml", include_str!("../languages/toml.toml")),
+    ("json", include_str!("../languages/json.toml")),
+    ("yaml", include_str!("../languages/yaml.toml")),
+    ("markdown", include_str!("../languages/markdown.toml")),
+    ("html", include_str!("../languages/html.toml")),
+    ("css", include_str!("../languages/css.toml")),
+    ("sql", include_str!("../languages/sql.toml")),
+];
+
+/// Delimitador de strings de un lenguaje
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StringRule {
+    /// Texto que abre y cierra el string (`"`, `'''`, `` ` ``)
+    pub delimiter: String,
+
+    /// El string puede continuar en las líneas siguientes
+    pub multiline: bool,
+}
+
+/// Reglas léxicas de un lenguaje
+#[derive(Clone, Debug, Default)]
+pub struct LanguageDef {
+    pub name: String,
+
+    /// Extensiones sin el punto (`rs`, `py`), sin distinguir mayúsculas
+    pub extensions: Vec<String>,
+
+    /// Patrones del nombre de archivo (`Makefile`, `*.mk`)
+    files: Vec<Regex>,
+
+    /// Marcadores de comentario hasta el final de la línea (`//`, `#`)
+    pub line_comments: Vec<String>,
+
+    /// Apertura y cierre de los comentarios de bloque (`/*`, `*/`)
+    pub block_comment: Option<(String, String)>,
+
+    /// Los comentarios de bloque se pueden anidar (como en Rust)
+    pub nested_comments: bool,
+
+    /// Delimitadores de strings, de más largo a más corto
+    pub strings: Vec<StringRule>,
+
+    /// Prefijo de los strings crudos (`r` en `r#"..."#`)
+    pub raw_string_prefix: Option<String>,
+
+    /// Carácter de escape dentro de los strings
+    pub escape: Option<char>,
+
+    /// Delimitadores de los strings sin escapes (`'` en shell)
+    pub unescaped_strings: Vec<String>,
+
+    /// `'` abre un literal de carácter o un lifetime (como en Rust)
+    pub char_literals: bool,
+
+    /// Aperturas de atributos que terminan en el `]` correspondiente (`#[`)
+    pub attributes: Vec<String>,
+
+    /// Carácter que inicia un decorador (`@` en Python)
+    pub decorator: Option<char>,
+
+    /// `ident!` es una invocación de macro
+    pub macros: bool,
+
+    pub keywords: Vec<String>,
+
+    /// Nombres de tipos además de los identificadores en CamelCase
+    pub types: Vec<String>,
+
+    /// Palabras clave seguidas por el nombre de una función (`fn`, `def`)
+    pub definition_keywords: Vec<String>,
+
+    /// Prefijos de números en otra base (`0x`, `0b`)
+    pub number_prefixes: Vec<String>,
+
+    /// Separador de dígitos (`_` en `1_000`)
+    pub number_separator: Option<char>,
+
+    /// Los números pueden terminar en un sufijo de tipo (`1u8`, `2.0f32`)
+    pub number_suffixes: bool,
+
+    /// Las palabras clave, tipos y definiciones no distinguen mayúsculas (SQL)
+    pub ignore_case: bool,
+
+    /// Marcadores que, al inicio de la línea, la convierten en un título
+    /// (`#` en Markdown, `[` en las tablas de TOML)
+    pub headings: Vec<String>,
+
+    /// Marcadores de directivas al inicio de la línea (`#` en `#include`)
+    pub directives: Vec<String>,
+
+    /// Delimitadores de énfasis (`**`, `_`), de más largo a más corto
+    pub emphasis: Vec<String>,
+
+    /// Separador entre clave y valor (`=` en TOML, `:` en JSON y YAML)
+    pub key_separator: Option<String>,
+
+    /// Carácter que inicia una variable (`$` en shell)
+    pub variable_prefix: Option<char>,
+
+    /// Delimitadores de los strings en los que se resaltan las variables
+    pub interpolated_strings: Vec<String>,
+
+    /// Etiquetas de HTML/XML (`<div>`, `</p>`); los strings solo se
+    /// reconocen dentro de una etiqueta, así un apóstrofo del texto no abre uno
+    pub tags: bool,
+
+    /// El texto es prosa: no se resaltan números, operadores ni identificadores
+    pub prose: bool,
+}
+
+impl LanguageDef {
+    /// Lee una definición en formato TOML
+    ///
+    /// # Argumentos
+    /// * `name` - Nombre a usar si el archivo no tiene la clave `name`
+    /// * `content` - Contenido del archivo
+    ///
+    /// # Retorna
+    /// La definición, o un mensaje de error si algún valor es inválido
+    pub fn parse(name: &str, content: &str) -> Result<Self, String> {
+        let mut def = LanguageDef {
+            name: name.to_string(),
+            ..LanguageDef::default()
+        };
+
+        for entry in parse_entries(content) {
+            if entry.section.is_empty() {
+                def.set(&entry.key, entry.value?)?;
+            }
+        }
+
+        Ok(def)
+    }
+
+    /// Asigna una clave de la definición
+    fn set(&mut self, key: &str, value: Value) -> Result<(), String> {
+        match key {
+            "name" => self.name = value.into_string(key)?,
+            "extensions" => {
+                self.extensions = value
+                    .into_list(key)?
+                    .into_iter()
+                    .map(|ext| ext.trim_start_matches('.').to_ascii_lowercase())
+                    .collect();
+            }
+            "files" => {
+                self.files = value
+                    .into_list(key)?
+                    .iter()
+                    .map(|glob| Regex::new(&format!("^{}$", finder::glob_to_regex(glob))))
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| format!("{}: patrón inválido: {}", key, e))?;
+            }
+            "line_comments" => self.line_comments = non_empty(key, value.into_list(key)?)?,
+            "block_comment" => match <[String; 2]>::try_from(value.into_list(key)?) {
+                Ok([open, close]) if !open.is_empty() && !close.is_empty() => {
+                    self.block_comment = Some((open, close));
+                }
+                _ => return Err(format!("{}: se esperaba [apertura, cierre]", key)),
+            },
+            "nested_comments" => self.nested_comments = value.into_bool(key)?,
+            "strings" | "multiline_strings" => {
+                let multiline = key == "multiline_strings";
+                self.strings.retain(|rule| rule.multiline != multiline);
+                for delimiter in non_empty(key, value.into_list(key)?)? {
+                    self.strings.push(StringRule {
+                        delimiter,
+                        multiline,
+                    });
+                }
+                // Los delimitadores más largos primero: `"""` antes que `"`
+                self.strings
+                    .sort_by_key(|rule| std::cmp::Reverse(rule.delimiter.chars().count()));
+            }
+            "raw_string_prefix" => {
+                self.raw_string_prefix = Some(value.into_string(key)?).filter(|p| !p.is_empty());
+            }
+            "escape" => self.escape = value.into_char(key)?,
+            "unescaped_strings" => self.unescaped_strings = value.into_list(key)?,
+            "char_literals" => self.char_literals = value.into_bool(key)?,
+            "attributes" => {
+                let openers = non_empty(key, value.into_list(key)?)?;
+                // `attribute_end` busca el `]` que cierra al `[` de la apertura
+                if openers.iter().any(|open| !open.ends_with('[')) {
+                    return Err(format!(
+                        "{}: cada apertura debe terminar en `[` (para `@nombre` usar `decorator`)",
+                        key
+                    ));
+                }
+                self.attributes = openers;
+            }
+            "decorator" => self.decorator = value.into_char(key)?,
+            "macros" => self.macros = value.into_bool(key)?,
+            "keywords" => self.keywords = value.into_list(key)?,
+            "types" => self.types = value.into_list(key)?,
+            "definition_keywords" => self.definition_keywords = value.into_list(key)?,
+            "number_prefixes" => self.number_prefixes = non_empty(key, value.into_list(key)?)?,
+            "number_separator" => self.number_separator = value.into_char(key)?,
+            "number_suffixes" => self.number_suffixes = value.into_bool(key)?,
+            "ignore_case" => self.ignore_case = value.into_bool(key)?,
+            "headings" => self.headings = non_empty(key, value.into_list(key)?)?,
+            "directives" => self.directives = non_empty(key, value.into_list(key)?)?,
+            "emphasis" => {
+                self.emphasis = non_empty(key, value.into_list(key)?)?;
+                self.emphasis
+                    .sort_by_key(|delimiter| std::cmp::Reverse(delimiter.chars().count()));
+            }
+            "key_separator" => {
+                self.key_separator = Some(value.into_string(key)?).filter(|s| !s.is_empty());
+            }
+            "variable_prefix" => self.variable_prefix = value.into_char(key)?,
+            "interpolated_strings" => self.interpolated_strings = value.into_list(key)?,
+            "tags" => self.tags = value.into_bool(key)?,
+            "prose" => self.prose = value.into_bool(key)?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Indica si la definición corresponde al nombre de archivo
+    pub fn matches_file(&self, filename: &str) -> bool {
+        let path = Path::new(filename);
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+        ext.is_some_and(|ext| self.extensions.contains(&ext))
+            || self.files.iter().any(|pattern| pattern.is_match(name))
+    }
+
+    pub fn is_keyword(&self, word: &str) -> bool {
+        self.contains_word(&self.keywords, word)
+    }
+
+    pub fn is_type(&self, word: &str) -> bool {
+        self.contains_word(&self.types, word)
+    }
+
+    pub fn is_definition_keyword(&self, word: &str) -> bool {
+        self.contains_word(&self.definition_keywords, word)
+    }
+
+    fn contains_word(&self, list: &[String], word: &str) -> bool {
+        if self.ignore_case {
+            list.iter().any(|w| w.eq_ignore_ascii_case(word))
+        } else {
+            list.iter().any(|w| w == word)
+        }
+    }
+}
+
+/// Lenguajes conocidos por el editor
+#[derive(Clone, Debug)]
+pub struct Languages {
+    defs: Vec<LanguageDef>,
+
+    /// Definiciones del usuario que no se pudieron cargar, con el motivo
+    errors: Vec<String>,
+}
+
+impl Languages {
+    /// Solo los lenguajes incluidos en el editor
+    pub fn builtin() -> Self {
+        let defs = BUILTIN
+            .iter()
+            .map(|(name, content)| {
+                LanguageDef::parse(name, content).expect("definición de lenguaje incluida inválida")
+            })
+            .collect();
+        Languages {
+            defs,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Errores de las definiciones del usuario que se ignoraron
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// Agrega un lenguaje, reemplazando al que tenga el mismo nombre
+    pub fn add(&mut self, def: LanguageDef) {
+        self.defs.retain(|existing| existing.name != def.name);
+        self.defs.push(def);
+    }
+
+    /// Lenguaje de un archivo según su nombre, o `None` para texto plano
+    ///
+    /// Si varios coinciden gana el último agregado, así las definiciones
+    /// del usuario tienen prioridad sobre las incluidas.
+    pub fn detect(&self, filename: Option<&str>) -> Option<&LanguageDef> {
+        let filename = filename?;
+        self.defs
+            .iter()
+            .rev()
+            .find(|def| def.matches_file(filename))
+    }
+}
+
+/// Carga los lenguajes incluidos más los definidos por el usuario
+///
+/// Las definiciones inválidas se ignoran para que un error en la
+/// configuración no impida abrir el editor; el motivo queda en
+/// `Languages::errors`.
+pub fn load_languages() -> Languages {
+    let mut languages = Languages::builtin();
+
+    if let Some(dir) = config::languages_dir() {
+        let (defs, errors) = load_language_dir(&dir);
+        for def in defs {
+            languages.add(def);
+        }
+        languages.errors.extend(errors);
+    }
+
+    if let Some(content) = config::find_config_path().and_then(|p| fs::read_to_string(p).ok()) {
+        let (defs, errors) = parse_config_languages(&content);
+        for def in defs {
+            languages.add(def);
+        }
+        languages.errors.extend(errors);
+    }
+
+    languages
+}
+
+/// Lee los archivos `*.toml` de un directorio, en orden alfabético
+///
+/// # Retorna
+/// Las definiciones válidas y un mensaje por cada archivo que no se pudo cargar
+fn load_language_dir(dir: &Path) -> (Vec<LanguageDef>, Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return (Vec::new(), Vec::new());
+    };
+
+    let mut paths: Vec<_> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+
+    let mut defs = Vec::new();
+    let mut errors = Vec::new();
+    for path in &paths {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let result = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| LanguageDef::parse(&name, &content));
+
+        match result {
+            Ok(def) => defs.push(def),
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    (defs, errors)
+}
+
+/// Lenguajes definidos en secciones `[languages.<nombre>]` de la configuración
+///
+/// # Retorna
+/// Las definiciones válidas y un mensaje por cada sección ignorada
+fn parse_config_languages(content: &str) -> (Vec<LanguageDef>, Vec<String>) {
+    let mut defs: Vec<(LanguageDef, Option<String>)> = Vec::new();
+    for entry in parse_entries(content) {
+        let Some(name) = entry.section.strip_prefix("languages.") else {
+            continue;
+        };
+
+        if defs.last().is_none_or(|(def, _)| def.name != name) {
+            let def = LanguageDef {
+                name: name.to_string(),
+                ..LanguageDef::default()
+            };
+            defs.push((def, None));
+        }
+
+        // Se informa el primer error de cada sección
+        let (def, error) = defs.last_mut().expect("sección recién agregada");
+        if let Err(e) = entry.value.and_then(|value| def.set(&entry.key, value))
+            && error.is_none()
+        {
+            *error = Some(format!("[languages.{}] {}", name, e));
+        }
+    }
+
+    let mut valid = Vec::new();
+    let mut errors = Vec::new();
+    for (def, error) in defs {
+        match error {
+            None => valid.push(def),
+            Some(e) => errors.push(e),
+        }
+    }
+    (valid, errors)
+}
+
+/// Valor de una clave: string, booleano o lista de strings
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Value {
+    String(String),
+    Bool(bool),
+    List(Vec<String>),
+}
+
+impl Value {
+    fn into_string(self, key: &str) -> Result<String, String> {
+        match self {
+            Value::String(s) => Ok(s),
+            _ => Err(format!("{}: se esperaba un texto", key)),
+        }
+    }
+
+    fn into_bool(self, key: &str) -> Result<bool, String> {
+        match self {
+            Value::Bool(b) => Ok(b),
+            _ => Err(format!("{}: se esperaba true o false", key)),
+        }
+    }
+
+    fn into_list(self, key: &str) -> Result<Vec<String>, String> {
+        match self {
+            Value::List(list) => Ok(list),
+            Value::String(s) => Ok(vec![s]),
+            Value::Bool(_) => Err(format!("{}: se esperaba una lista", key)),
+        }
+    }
+
+    /// Un único carácter; el texto vacío equivale a ninguno
+    fn into_char(self, key: &str) -> Result<Option<char>, String> {
+        let s = self.into_string(key)?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (first, None) => Ok(first),
+            _ => Err(format!("{}: se esperaba un solo carácter", key)),
+        }
+    }
+}
+
+fn non_empty(key: &str, list: Vec<String>) -> Result<Vec<String>, String> {
+    if list.iter().any(String::is_empty) {
+        return Err(format!("{}: no se admiten textos vacíos", key));
+    }
+    Ok(list)
+}
+
+/// Clave de un archivo TOML con la sección en la que aparece
+struct Entry {
+    section: String,
+    key: String,
+
+    /// Valor, o un mensaje de error con el número de línea
+    value: Result<Value, String>,
+}
+
+/// Lee las claves de un archivo TOML simple
+///
+/// Admite secciones `[nombre]`, strings con comillas dobles (con escapes) o
+/// simples (literales), `true` / `false` y listas de strings, que pueden
+/// ocupar varias líneas. Los valores inválidos se devuelven como error en
+/// su entrada, así una sección no afecta a las demás.
+fn parse_entries(content: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut section = String::new();
+    let mut lines = content.lines().enumerate();
+
+    while let Some((idx, raw_line)) = lines.next() {
+        let line = config::strip_inline_comment(raw_line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line[1..line.len() - 1].trim().to_string();
+            continue;
+        }
+
+        let error = |msg: &str| Err(format!("línea {}: {}", idx + 1, msg));
+        let Some((key, value)) = line.split_once('=') else {
+            entries.push(Entry {
+                section: section.clone(),
+                key: String::new(),
+                value: error("se esperaba `clave = valor`"),
+            });
+            continue;
+        };
+
+        // Las listas continúan hasta el `]` que las cierra
+        let mut text = value.trim().to_string();
+        let mut closed = true;
+        while text.starts_with('[') && !list_closed(&text) {
+            let Some((_, next)) = lines.next() else {
+                closed = false;
+                break;
+            };
+            text.push(' ');
+            text.push_str(config::strip_inline_comment(next).trim());
+        }
+
+        let value = if !closed {
+            error("lista sin cerrar")
+        } else {
+            parse_value(&text).map_or_else(|| error("valor inválido"), Ok)
+        };
+        entries.push(Entry {
+            section: section.clone(),
+            key: key.trim().to_string(),
+            value,
+        });
+    }
+
+    entries
+}
+
+/// Indica si el texto tiene el `]` que cierra la lista, fuera de los strings
+fn list_closed(text: &str) -> bool {
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => while chars.next().is_some_and(|c| c != '"') {},
+            '\'' => while chars.next().is_some_and(|c| c != '\'') {},
+            ']' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+fn parse_value(text: &str) -> Option<Value> {
+    match text {
+        "true" => return Some(Value::Bool(true)),
+        "false" => return Some(Value::Bool(false)),
+        _ => {}
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut pos = 0;
+
+    if chars.first() != Some(&'[') {
+        let s = parse_string(&chars, &mut pos)?;
+        return (pos == chars.len()).then_some(Value::String(s));
+    }
+
+    pos += 1;
+    let mut list = Vec::new();
+    loop {
+        skip_spaces(&chars, &mut pos);
+        match chars.get(pos)? {
+            ']' => {
+                pos += 1;
+                break;
+            }
+            _ => list.push(parse_string(&chars, &mut pos)?),
+        }
+
+        skip_spaces(&chars, &mut pos);
+        match chars.get(pos)? {
+            ',' => pos += 1,
+            ']' => {
+                pos += 1;
+                break;
+            }
+            _ => return None,
+        }
+    }
+
+    (pos == chars.len()).then_some(Value::List(list))
+}
+
+fn skip_spaces(chars: &[char], pos: &mut usize) {
+    while chars.get(*pos).is_some_and(|c| c.is_whitespace()) {
+        *pos += 1;
+    }
+}
+
+/// Lee un string entre comillas que empieza en `pos` y avanza hasta después
+fn parse_string(chars: &[char], pos: &mut usize) -> Option<String> {
+    let quote = *chars.get(*pos).filter(|&&c| c == '"' || c == '\'')?;
+    let mut s = String::new();
+    *pos += 1;
+
+    loop {
+        let ch = *chars.get(*pos)?;
+        *pos += 1;
+        match ch {
+            c if c == quote => return Some(s),
+            '\\' if quote == '"' => {
+                let escaped = *chars.get(*pos)?;
+                *pos += 1;
+                s.push(match escaped {
+                    'n' => '\n',
+                    't' => '\t',
+                    c => c,
+                });
+            }
+            c => s.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_languages_parse_and_detect_by_extension() {
+        let languages = Languages::builtin();
+
+        let name = |file| languages.detect(Some(file)).map(|def| def.name.as_str());
+        assert_eq!(name("main.rs"), Some("rust"));
+        assert_eq!(name("app.PY"), Some("python"));
+        assert_eq!(name("app.js"), Some("javascript"));
+        assert_eq!(name("app.ts"), Some("typescript"));
+        assert_eq!(name("main.go"), Some("go"));
+        assert_eq!(name("lib.h"), Some("c"));
+        assert_eq!(name("lib.hpp"), Some("cpp"));
+        assert_eq!(name("Cargo.toml"), Some("toml"));
+        assert_eq!(name("Cargo.lock"), Some("toml"));
+        assert_eq!(name("ci.yml"), Some("yaml"));
+        assert_eq!(name("package.json"), Some("json"));
+        assert_eq!(name("README.md"), Some("markdown"));
+        assert_eq!(name("/home/ana/.bashrc"), Some("shell"));
+        assert_eq!(name("index.html"), Some("html"));
+        assert_eq!(name("style.css"), Some("css"));
+        assert_eq!(name("schema.sql"), Some("sql"));
+        assert_eq!(name("notes.txt"), None);
+        assert_eq!(languages.detect(None).map(|def| &def.name), None);
+
+        let rust = languages.detect(Some("lib.rs")).unwrap();
+        assert_eq!(
+            rust.block_comment,
+            Some(("/*".to_string(), "*/".to_string()))
+        );
+        assert_eq!(rust.escape, Some('\\'));
+        assert!(rust.is_keyword("fn"));
+        assert!(rust.types.contains(&"usize".to_string()));
+    }
+
+    #[test]
+    fn parse_values_and_multiline_lists() {
+        let content = r#"
+            name = "go"   # comentario
+            files = ["*.go", 'go.mod']
+            keywords = [
+                "func", "var",  # comentario
+                "type",
+            ]
+            strings = ['"', "'"]
+            multiline_strings = "`"
+            escape = "\\"
+            nested_comments = false
+        "#;
+
+        let def = LanguageDef::parse("archivo", content).unwrap();
+
+        assert_eq!(def.name, "go");
+        assert_eq!(def.keywords, ["func", "var", "type"]);
+        assert_eq!(def.escape, Some('\\'));
+        assert!(def.matches_file("cmd/main.go"));
+        assert!(def.matches_file("go.mod"));
+        assert!(!def.matches_file("go.sum"));
+        assert_eq!(
+            def.strings
+                .iter()
+                .map(|rule| (rule.delimiter.as_str(), rule.multiline))
+                .collect::<Vec<_>>(),
+            [("\"", false), ("'", false), ("`", true)]
+        );
+    }
+
+    #[test]
+    fn longer_string_delimiters_come_first() {
+        let def =
+            LanguageDef::parse("py", "strings = ['\"']\nmultiline_strings = ['\"\"\"']").unwrap();
+
+        assert_eq!(def.strings[0].delimiter, "\"\"\"");
+        assert_eq!(def.strings[1].delimiter, "\"");
+    }
+
+    #[test]
+    fn invalid_definitions_report_the_line() {
+        assert_eq!(
+            LanguageDef::parse("x", "name = \"x\"\nescape = \"ab\"").unwrap_err(),
+            "escape: se esperaba un solo carácter"
+        );
+        assert_eq!(
+            LanguageDef::parse("x", "\nkeywords = [\"a\"").unwrap_err(),
+            "línea 2: lista sin cerrar"
+        );
+        assert!(LanguageDef::parse("x", "block_comment = [\"/*\"]").is_err());
+        assert!(
+            LanguageDef::parse("x", "attributes = [\"@\"]")
+                .unwrap_err()
+                .contains("decorator")
+        );
+        assert!(LanguageDef::parse("x", "attributes = [\"#[\", \"#![\"]").is_ok());
+        assert!(LanguageDef::parse("x", "macros = \"si\"").is_err());
+    }
+
+    #[test]
+    fn config_sections_define_and_replace_languages() {
+        let content = r##"
+            [syntax]
+            keyword = "#112233"
+
+            [languages.rust]
+            extensions = ["rs"]
+            keywords = ["fn"]
+
+            [languages.lua]
+            extensions = ["lua"]
+            line_comments = ["--"]
+
+            [languages.mala]
+            extensions = ["x"]
+            escape = "ab"
+        "##;
+
+        let mut languages = Languages::builtin();
+        let (defs, errors) = parse_config_languages(content);
+        for def in defs {
+            languages.add(def);
+        }
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("[languages.mala] escape"));
+
+        let rust = languages.detect(Some("main.rs")).unwrap();
+        assert_eq!(rust.keywords, ["fn"]);
+        assert_eq!(rust.line_comments, Vec::<String>::new());
+        assert_eq!(
+            languages.detect(Some("init.lua")).unwrap().line_comments,
+            ["--"]
+        );
+        assert!(languages.detect(Some("archivo.x")).is_none());
+    }
+
+    #[test]
+    fn language_dir_uses_file_stem_as_default_name() {
+        let dir = std::env::temp_dir().join(format!("hyperion_languages_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("toml.toml"), "extensions = [\"toml\"]\n").unwrap();
+        fs::write(dir.join("rota.toml"), "extensions = [\n").unwrap();
+        fs::write(dir.join("notas.txt"), "extensions = [\"txt\"]\n").unwrap();
+
+        let (defs, errors) = load_language_dir(&dir);
+
+        assert_eq!(defs.len(), 1);
+        assert_eq!(defs[0].name, "toml");
+        assert!(defs[0].matches_file("Cargo.toml"));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("rota.toml"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,37 +1,91 @@
 mod buffer;
 mod cli;
+mod command;
+mod completion;
 mod config;
 mod editor;
+mod encoding;
+mod file_io;
+mod finder;
+mod grep;
+mod history;
+mod language;
+mod prompt;
 mod search;
+mod swap;
 mod syntax;
 mod terminal;
 mod ui;
+mod undo;
 
-use crossterm::event::{Event, KeyCode};
-use std::io::Write;
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+use std::io::{self, IsTerminal, Read, Write};
 
 use crate::{
-    cli::Args,
+    cli::{Args, FileTarget},
+    command::Command,
     editor::Editor,
-    terminal::{clear_screen, keys, messages, request_input},
+    history::{Histories, HistoryKind},
+    swap::SWAP_INTERVAL,
+    terminal::{
+        clear_screen, keys, messages, request_choice, request_fuzzy, request_input,
+        request_input_incremental, request_path, request_selection,
+    },
 };
 
 fn main() {
     let args = Args::parse_args();
+    let targets = args.targets(io::stdin().is_terminal());
+
+    // La entrada estándar se lee completa antes de pasar a modo raw; el
+    // teclado se sigue leyendo desde la terminal (/dev/tty)
+    let mut stdin_content = targets.iter().any(FileTarget::is_stdin).then(read_stdin);
 
     let mut stdout = terminal::init_raw_mode().unwrap();
 
     let mut editor = Editor::new();
+    let mut histories = load_histories(&editor);
 
-    // Si se proporcionó un archivo, intentar abrirlo o preparar para crearlo
-    if let Some(filepath) = args.file {
-        if std::path::Path::new(&filepath).exists() {
-            editor.open_file(&filepath);
+    // Abrir cada archivo indicado en un buffer, o prepararlo para crearlo
+    for target in &targets {
+        if target.is_stdin() {
+            match stdin_content.take() {
+                Some(Ok(bytes)) => editor.open_stdin(&bytes),
+                Some(Err(e)) => {
+                    editor.state_msg = format!("Error al leer la entrada estándar: {}", e);
+                    continue;
+                }
+                None => continue,
+            }
+        } else if std::path::Path::new(&target.path).exists() {
+            // Si no se pudo abrir, el buffer activo es otro: no se le aplican
+            // las opciones de este archivo
+            if !editor.open_file(&target.path) {
+                continue;
+            }
+            handle_recovery(&mut editor, &mut stdout);
         } else {
-            editor.filename = Some(filepath.clone());
-            editor.state_msg = format!("Nuevo archivo: '{}' (Ctrl+S para guardar)", filepath);
+            editor.new_file(&target.path);
         }
-    } else {
+
+        if args.readonly {
+            editor.set_read_only(true);
+        }
+
+        if let Some(line) = target.line {
+            let column = target.column.unwrap_or(1);
+            editor.go_to_line((line.saturating_sub(1), column.saturating_sub(1)));
+            editor.adjust_scroll();
+        }
+    }
+
+    if targets.len() > 1 {
+        editor.switch_buffer(0);
+    }
+    // Después de abrir los archivos, que reemplazan el mensaje de estado
+    editor.report_language_errors();
+
+    if targets.is_empty() {
         clear_screen(&mut stdout);
         write!(
             stdout,
@@ -43,9 +97,25 @@ fn main() {
 
     editor.write(&mut stdout);
 
-    // Leer entrada de usuario
-    while let Ok(event) = terminal::read_event() {
+    // Leer entrada de usuario; si no llega nada en un rato se actualiza la recuperación
+    while let Ok(event) = terminal::poll_event(SWAP_INTERVAL) {
+        let Some(event) = event else {
+            editor.update_swap();
+            continue;
+        };
+
         match event {
+            Event::Paste(text) => {
+                editor.insert_text(&text);
+                editor.adjust_scroll();
+                editor.write(&mut stdout);
+                continue;
+            }
+            Event::FocusGained => {
+                check_external_change(&mut editor, &mut stdout);
+                editor.write(&mut stdout);
+                continue;
+            }
             Event::Resize(width, height) => {
                 editor.update_window_size(width, height);
                 editor.adjust_scroll();
@@ -65,37 +135,55 @@ fn main() {
                 }
 
                 if keys::is_quit(&key) {
-                    break;
+                    if confirm_quit(&mut editor, &mut stdout, &mut histories) {
+                        break;
+                    }
                 } else if keys::is_save(&key) {
-                    let path = match &editor.filename {
-                        Some(name) => name.clone(),
-                        None => {
-                            let name = request_input(&mut stdout, "Guardar como: ");
-                            if name.is_empty() {
-                                editor.state_msg = messages::SAVE_CANCELLED.to_string();
-                                editor.write(&mut stdout);
-                                continue;
-                            }
-                            name
-                        }
-                    };
-                    editor.save_file(&path);
+                    save_buffer(&mut editor, &mut stdout, &mut histories);
                 } else if keys::is_open(&key) {
-                    let path = request_input(&mut stdout, "Abrir archivo: ");
-                    if !path.is_empty() {
-                        editor.open_file(&path);
-                    } else {
-                        editor.state_msg = messages::OPEN_CANCELLED.to_string();
+                    let default = editor.current_dir_hint();
+                    match request_path(
+                        &mut stdout,
+                        "Abrir archivo: ",
+                        &default,
+                        histories.get_mut(HistoryKind::File),
+                    ) {
+                        Some(path) if !path.is_empty() && path != default => {
+                            editor.open_file(&expand_home(&path));
+                            handle_recovery(&mut editor, &mut stdout);
+                        }
+                        _ => editor.state_msg = messages::OPEN_CANCELLED.to_string(),
                     }
+                } else if keys::is_find_file(&key) {
+                    find_file(&mut editor, &mut stdout);
                 } else if keys::is_search(&key) {
-                    let query = request_input(&mut stdout, "Buscar: ");
-                    editor.search(&query);
+                    editor.begin_incremental_search();
+                    let query = request_input_incremental(
+                        &mut stdout,
+                        messages::SEARCH_PROMPT,
+                        histories.get_mut(HistoryKind::Search),
+                        |out, input| {
+                            editor.update_incremental_search(input);
+                            editor.write(out);
+                        },
+                    );
+                    editor.finish_incremental_search(query.as_deref());
+                } else if keys::is_replace(&key) {
+                    run_replace(&mut editor, &mut stdout, &mut histories);
                 } else if keys::is_next_match(&key) {
                     editor.next_match();
                 } else if keys::is_prev_match(&key) {
                     editor.previous_match();
                 } else if keys::is_goto_line(&key) {
-                    let coords_str = request_input(&mut stdout, "Ir a (linea, columna): ");
+                    let Some(coords_str) = request_input(
+                        &mut stdout,
+                        "Ir a (linea, columna): ",
+                        histories.get_mut(HistoryKind::Goto),
+                    ) else {
+                        editor.state_msg = messages::GOTO_CANCELLED.to_string();
+                        editor.write(&mut stdout);
+                        continue;
+                    };
 
                     let parts: Vec<&str> = coords_str.split(',').collect();
 
@@ -121,34 +209,426 @@ fn main() {
                         }
                     }
                 } else if keys::is_copy(&key) {
-                    editor.copy_line();
+                    editor.copy();
+                } else if keys::is_cut(&key) {
+                    editor.cut();
                 } else if keys::is_paste(&key) {
                     editor.paste_clipboard();
+                } else if keys::is_command(&key) {
+                    match request_input(
+                        &mut stdout,
+                        "Comando: ",
+                        histories.get_mut(HistoryKind::Command),
+                    ) {
+                        Some(input) if !input.trim().is_empty() => {
+                            match command::parse_command(&input) {
+                                Ok(Command::SearchInFiles(query)) => {
+                                    search_in_files(&mut editor, &mut stdout, &mut histories, query)
+                                }
+                                Ok(command) => editor.execute_command(command),
+                                Err(e) => editor.state_msg = e,
+                            }
+                        }
+                        _ => editor.state_msg = messages::COMMAND_CANCELLED.to_string(),
+                    }
+                } else if keys::is_buffer_list(&key) {
+                    let names = editor.buffer_names();
+                    if let Some(idx) = request_selection(
+                        &mut stdout,
+                        messages::BUFFER_LIST_TITLE,
+                        &names,
+                        editor.active_buffer(),
+                    ) {
+                        editor.switch_buffer(idx);
+                        check_external_change(&mut editor, &mut stdout);
+                        handle_recovery(&mut editor, &mut stdout);
+                    }
+                } else if keys::is_next_buffer(&key) {
+                    editor.next_buffer();
+                    check_external_change(&mut editor, &mut stdout);
+                    handle_recovery(&mut editor, &mut stdout);
+                } else if keys::is_prev_buffer(&key) {
+                    editor.previous_buffer();
+                    check_external_change(&mut editor, &mut stdout);
+                    handle_recovery(&mut editor, &mut stdout);
+                } else if keys::is_close_buffer(&key) {
+                    if confirm_close_buffer(&mut editor, &mut stdout, &mut histories) {
+                        editor.close_buffer();
+                        handle_recovery(&mut editor, &mut stdout);
+                    }
+                } else if keys::is_undo(&key) {
+                    editor.undo();
+                } else if keys::is_redo(&key) {
+                    editor.redo();
                 } else {
+                    let selecting = key.modifiers.contains(KeyModifiers::SHIFT);
                     match key.code {
-                        KeyCode::Up => editor.move_up(),
-                        KeyCode::Down => editor.move_down(),
-                        KeyCode::Left => editor.move_left(),
-                        KeyCode::Right => editor.move_right(),
-                        KeyCode::Home => editor.move_to_line_start(),
-                        KeyCode::End => editor.move_to_line_end(),
-                        KeyCode::PageUp => editor.move_page_up(),
-                        KeyCode::PageDown => editor.move_page_down(),
+                        KeyCode::Up => editor.move_with_selection(Editor::move_up, selecting),
+                        KeyCode::Down => editor.move_with_selection(Editor::move_down, selecting),
+                        KeyCode::Left => editor.move_with_selection(Editor::move_left, selecting),
+                        KeyCode::Right => editor.move_with_selection(Editor::move_right, selecting),
+                        KeyCode::Home => {
+                            editor.move_with_selection(Editor::move_to_line_start, selecting)
+                        }
+                        KeyCode::End => {
+                            editor.move_with_selection(Editor::move_to_line_end, selecting)
+                        }
+                        KeyCode::PageUp => {
+                            editor.move_with_selection(Editor::move_page_up, selecting)
+                        }
+                        KeyCode::PageDown => {
+                            editor.move_with_selection(Editor::move_page_down, selecting)
+                        }
                         KeyCode::Tab => editor.insert_tab(),
                         KeyCode::Enter => editor.new_line(),
                         KeyCode::Backspace => editor.delete_char(),
                         KeyCode::Delete => editor.delete_forward_char(),
                         KeyCode::Char(c) => editor.insert_char(c),
+                        KeyCode::Esc => editor.clear_selection(),
                         _ => {}
                     }
                 }
 
+                editor.update_swap();
                 editor.adjust_scroll();
                 editor.write(&mut stdout);
             }
             _ => {}
         }
     }
+    editor.remove_all_swaps();
+    let history_result = histories.save();
     clear_screen(&mut stdout);
     terminal::cleanup().unwrap();
+
+    // Con el terminal ya restaurado, para que el mensaje no quede desalineado
+    if let Err(e) = history_result {
+        eprintln!("No se pudo guardar el historial: {}", e);
+    }
+}
+
+/// Carga los historiales de los prompts
+///
+/// Solo se leen y guardan en disco si está habilitado `history` en la
+/// sección `[editor]` de la configuración.
+fn load_histories(editor: &Editor) -> Histories {
+    if !editor.settings().history {
+        return Histories::in_memory();
+    }
+
+    history::history_file_path()
+        .map(Histories::load)
+        .unwrap_or_else(Histories::in_memory)
+}
+
+/// Expande `~` en una ruta ingresada en un prompt
+fn expand_home(path: &str) -> String {
+    completion::expand_home(path).to_string_lossy().into_owned()
+}
+
+/// Abre un archivo del proyecto elegido con el buscador difuso
+fn find_file<W: Write>(editor: &mut Editor, stdout: &mut W) {
+    let root = std::env::current_dir().unwrap_or_else(|_| ".".into());
+    let files = finder::project_files(&root);
+    if files.is_empty() {
+        editor.state_msg = messages::NO_FILES_FOUND.to_string();
+        return;
+    }
+
+    let title = format!(
+        "Buscar archivo en {} ({} archivos; Enter: abrir, Esc: cancelar)",
+        root.display(),
+        files.len()
+    );
+    let rows = crossterm::terminal::size().map_or(24, |(_, height)| height as usize);
+    let choice = request_fuzzy(stdout, &title, messages::FIND_FILE_PROMPT, &files, |idx| {
+        finder::preview(&root.join(&files[idx]), rows)
+    });
+
+    match choice {
+        Some(idx) => {
+            editor.open_file(&files[idx]);
+            handle_recovery(editor, stdout);
+        }
+        None => editor.state_msg = messages::OPEN_CANCELLED.to_string(),
+    }
+}
+
+/// Busca en los archivos del proyecto y abre el resultado elegido de la lista
+///
+/// # Argumentos
+/// * `query` - Texto a buscar; si está vacío se pide en un prompt
+fn search_in_files<W: Write>(
+    editor: &mut Editor,
+    stdout: &mut W,
+    histories: &mut Histories,
+    query: String,
+) {
+    let history = histories.get_mut(HistoryKind::Search);
+    let query = if query.is_empty() {
+        match request_input(stdout, messages::GREP_PROMPT, history) {
+            Some(query) if !query.is_empty() => query,
+            _ => {
+                editor.state_msg = messages::SEARCH_CANCELLED.to_string();
+                return;
+            }
+        }
+    } else {
+        history.push(&query);
+        query
+    };
+
+    let root = std::env::current_dir().unwrap_or_else(|_| ".".into());
+    let results = match grep::search_project(&root, &query) {
+        Ok(results) => results,
+        Err(e) => {
+            editor.state_msg = e;
+            return;
+        }
+    };
+    if results.hits.is_empty() {
+        editor.state_msg = format!("No se encontró '{}' en los archivos del proyecto", query);
+        return;
+    }
+
+    let labels: Vec<String> = results.hits.iter().map(|hit| hit.label()).collect();
+    let title = format!(
+        "{} coincidencias de '{}'{} (Enter: abrir, Esc: cancelar)",
+        results.hits.len(),
+        query,
+        if results.truncated {
+            ", resultados truncados"
+        } else {
+            ""
+        }
+    );
+
+    let Some(idx) = request_selection(stdout, &title, &labels, 0) else {
+        editor.state_msg = messages::SEARCH_CANCELLED.to_string();
+        return;
+    };
+
+    let hit = &results.hits[idx];
+    if editor.open_file(&hit.path) {
+        handle_recovery(editor, stdout);
+        editor.show_search_hit(&query, hit.line, hit.column);
+    }
+}
+
+/// Lee toda la entrada estándar
+fn read_stdin() -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    io::stdin().read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Guarda el buffer actual, pidiendo un nombre si todavía no tiene uno
+///
+/// # Retorna
+/// `true` si el archivo quedó guardado
+fn save_buffer<W: Write>(editor: &mut Editor, stdout: &mut W, histories: &mut Histories) -> bool {
+    // Un buffer de solo lectura no sobrescribe su archivo: se guarda como otro
+    let path = match editor.filename().filter(|_| !editor.is_read_only()) {
+        Some(name) => name.to_string(),
+        None => match request_path(
+            stdout,
+            "Guardar como: ",
+            &editor.current_dir_hint(),
+            histories.get_mut(HistoryKind::File),
+        ) {
+            // Confirmar el directorio sugerido sin escribir un nombre es cancelar
+            Some(name)
+                if !name.is_empty() && !std::path::Path::new(&expand_home(&name)).is_dir() =>
+            {
+                expand_home(&name)
+            }
+            _ => {
+                editor.state_msg = messages::SAVE_CANCELLED.to_string();
+                return false;
+            }
+        },
+    };
+
+    // Los cambios externos solo importan si se va a sobrescribir el propio archivo
+    if editor.is_own_file(&path)
+        && editor.changed_on_disk()
+        && !resolve_external_change(editor, stdout)
+    {
+        return false;
+    }
+    editor.save_file(&path)
+}
+
+/// Si el archivo del buffer activo cambió en disco, pregunta qué hacer
+fn check_external_change<W: Write>(editor: &mut Editor, stdout: &mut W) {
+    if editor.changed_on_disk() {
+        resolve_external_change(editor, stdout);
+    }
+}
+
+/// Pregunta qué hacer cuando otro programa modificó el archivo abierto
+///
+/// # Retorna
+/// `true` si el usuario eligió sobrescribir el archivo con la versión del editor
+fn resolve_external_change<W: Write>(editor: &mut Editor, stdout: &mut W) -> bool {
+    editor.write(stdout);
+
+    match request_choice(stdout, messages::EXTERNAL_CHANGE_PROMPT, &['r', 's', 'a']) {
+        Some('r') => {
+            editor.reload_from_disk();
+            false
+        }
+        Some('s') => {
+            editor.ignore_external_change();
+            true
+        }
+        Some('a') => {
+            editor.keep_both_versions();
+            false
+        }
+        _ => {
+            editor.state_msg = messages::ACTION_CANCELLED.to_string();
+            false
+        }
+    }
+}
+
+/// Antes de salir, pide confirmación por cada buffer con cambios sin guardar
+///
+/// # Retorna
+/// `true` si se puede salir
+fn confirm_quit<W: Write>(editor: &mut Editor, stdout: &mut W, histories: &mut Histories) -> bool {
+    for idx in editor.modified_buffers() {
+        editor.switch_buffer(idx);
+        editor.adjust_scroll();
+        editor.write(stdout);
+
+        if !confirm_discard_changes(editor, stdout, histories, messages::UNSAVED_CHANGES_PROMPT) {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Pide confirmación antes de cerrar un buffer con cambios sin guardar
+fn confirm_close_buffer<W: Write>(
+    editor: &mut Editor,
+    stdout: &mut W,
+    histories: &mut Histories,
+) -> bool {
+    confirm_discard_changes(editor, stdout, histories, messages::CLOSE_BUFFER_PROMPT)
+}
+
+/// Pide confirmación antes de descartar cambios sin guardar del buffer activo
+///
+/// # Retorna
+/// `true` si se puede continuar (no había cambios, se guardaron o se descartaron)
+fn confirm_discard_changes<W: Write>(
+    editor: &mut Editor,
+    stdout: &mut W,
+    histories: &mut Histories,
+    prompt: &str,
+) -> bool {
+    if !editor.is_modified() {
+        return true;
+    }
+
+    match request_choice(stdout, prompt, &['s', 'n', 'c']) {
+        Some('s') => save_buffer(editor, stdout, histories),
+        Some('n') => true,
+        _ => {
+            editor.state_msg = messages::ACTION_CANCELLED.to_string();
+            false
+        }
+    }
+}
+
+/// Pregunta qué hacer con el archivo de recuperación encontrado al abrir
+fn handle_recovery<W: Write>(editor: &mut Editor, stdout: &mut W) {
+    while editor.has_pending_recovery() {
+        editor.write(stdout);
+
+        match request_choice(stdout, messages::RECOVERY_PROMPT, &['r', 'd', 'b']) {
+            Some('r') => editor.recover_from_swap(),
+            Some('d') => {
+                // Se recorre con las flechas; Enter o Esc vuelven a la pregunta
+                request_selection(
+                    stdout,
+                    messages::RECOVERY_DIFF_TITLE,
+                    &editor.recovery_diff(),
+                    0,
+                );
+            }
+            Some('b') => editor.delete_recovery(),
+            _ => editor.keep_recovery(),
+        }
+    }
+}
+
+/// Ejecuta el flujo interactivo de buscar y reemplazar
+///
+/// Recorre cada coincidencia preguntando si reemplazarla; la opción
+/// "todas" reemplaza las restantes en un único paso de deshacer.
+fn run_replace<W: Write>(editor: &mut Editor, stdout: &mut W, histories: &mut Histories) {
+    if editor.is_read_only() {
+        editor.state_msg = messages::READ_ONLY.to_string();
+        return;
+    }
+
+    let query = match request_input(
+        stdout,
+        messages::REPLACE_SEARCH_PROMPT,
+        histories.get_mut(HistoryKind::Search),
+    ) {
+        Some(query) if !query.is_empty() => query,
+        _ => {
+            editor.state_msg = messages::REPLACE_CANCELLED.to_string();
+            return;
+        }
+    };
+    let Some(replacement) = request_input(
+        stdout,
+        "Reemplazar con: ",
+        histories.get_mut(HistoryKind::Replace),
+    ) else {
+        editor.state_msg = messages::REPLACE_CANCELLED.to_string();
+        return;
+    };
+
+    editor.search(&query);
+    if !editor.prepare_replacement(&replacement) {
+        return;
+    }
+    let mut remaining = editor.match_count();
+    let mut replaced = 0;
+
+    while remaining > 0 {
+        editor.adjust_scroll();
+        editor.write(stdout);
+
+        match request_choice(stdout, messages::REPLACE_PROMPT, &['s', 'n', 't', 'q']) {
+            Some('s') => {
+                let before = editor.match_count();
+                if !editor.replace_current(&replacement) {
+                    break;
+                }
+                remaining = remaining.saturating_sub(before - editor.match_count());
+                replaced += 1;
+            }
+            Some('n') => {
+                remaining -= 1;
+                editor.next_match();
+            }
+            Some('t') => {
+                replaced += editor.replace_matches(&replacement, remaining);
+                break;
+            }
+            _ => break,
+        }
+    }
+
+    if replaced > 0 || editor.match_count() > 0 {
+        editor.state_msg = format!("Reemplazadas {} coincidencias", replaced);
+    }
 }
@@ -0,0 +1,389 @@
+//! Edición de una línea de texto en los prompts de la barra inferior
+//!
+//! `LineEditor` mantiene el texto y la posición del cursor y traduce las
+//! teclas en acciones de edición; `render` lo dibuja en una fila de la
+//! terminal desplazándolo horizontalmente para que el cursor quede visible.
+//! `HistoryBrowser` recorre el historial del prompt con las flechas.
+
+use std::io::Write;
+
+use crossterm::{
+    cursor,
+    event::{KeyCode, KeyEvent, KeyModifiers},
+};
+use unicode_width::UnicodeWidthChar;
+
+use crate::ui;
+
+/// Resultado de procesar una tecla en el prompt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PromptAction {
+    /// Enter: confirmar el texto
+    Submit,
+
+    /// Esc: cancelar el prompt
+    Cancel,
+
+    /// El texto cambió
+    Edited,
+
+    /// Solo se movió el cursor
+    Moved,
+
+    /// La tecla no tiene efecto en el prompt
+    Ignored,
+}
+
+/// Texto editable de una sola línea con cursor
+#[derive(Clone, Debug, Default)]
+pub struct LineEditor {
+    chars: Vec<char>,
+
+    /// Posición del cursor, en caracteres
+    cursor: usize,
+}
+
+impl LineEditor {
+    /// Crea el editor con un valor inicial y el cursor al final
+    pub fn new(default: &str) -> Self {
+        let mut editor = LineEditor::default();
+        editor.insert_str(default);
+        editor
+    }
+
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// Reemplaza todo el texto, dejando el cursor al final
+    pub fn set_text(&mut self, text: &str) {
+        self.chars.clear();
+        self.cursor = 0;
+        self.insert_str(text);
+    }
+
+    /// Procesa una tecla
+    ///
+    /// Además de las flechas, `Home`/`End`, `Backspace` y `Delete`, acepta
+    /// los atajos habituales de la línea de comandos: `Ctrl+A`/`Ctrl+E`
+    /// (inicio/fin), `Ctrl+K`/`Ctrl+U` (borrar hasta el fin/inicio),
+    /// `Ctrl+W` (borrar la palabra anterior) y `Ctrl+Flechas` (por palabras).
+    pub fn handle_key(&mut self, key: &KeyEvent) -> PromptAction {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Enter => PromptAction::Submit,
+            KeyCode::Esc => PromptAction::Cancel,
+            KeyCode::Char('a') if control => self.move_to(0),
+            KeyCode::Char('e') if control => self.move_to(self.chars.len()),
+            KeyCode::Char('k') if control => self.delete_range(self.cursor, self.chars.len()),
+            KeyCode::Char('u') if control => self.delete_range(0, self.cursor),
+            KeyCode::Char('w') if control => self.delete_range(self.word_start(), self.cursor),
+            KeyCode::Char(_) if control => PromptAction::Ignored,
+            KeyCode::Char(c) => {
+                self.insert_char(c);
+                PromptAction::Edited
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.delete_range(self.cursor - 1, self.cursor)
+            }
+            KeyCode::Delete if self.cursor < self.chars.len() => {
+                self.delete_range(self.cursor, self.cursor + 1)
+            }
+            KeyCode::Left if control => self.move_to(self.word_start()),
+            KeyCode::Right if control => self.move_to(self.word_end()),
+            KeyCode::Left => self.move_to(self.cursor.saturating_sub(1)),
+            KeyCode::Right => self.move_to((self.cursor + 1).min(self.chars.len())),
+            KeyCode::Home => self.move_to(0),
+            KeyCode::End => self.move_to(self.chars.len()),
+            _ => PromptAction::Ignored,
+        }
+    }
+
+    /// Inserta texto pegado en la posición del cursor
+    ///
+    /// Los saltos de línea finales se descartan y los intermedios se
+    /// convierten en espacios, porque el prompt tiene una sola línea.
+    pub fn insert_str(&mut self, text: &str) {
+        let text = text.trim_end_matches(['\r', '\n']);
+        for c in text.chars().filter(|&c| c != '\r') {
+            self.insert_char(if c == '\n' { ' ' } else { c });
+        }
+    }
+
+    fn insert_char(&mut self, c: char) {
+        self.chars.insert(self.cursor, c);
+        self.cursor += 1;
+    }
+
+    fn delete_range(&mut self, start: usize, end: usize) -> PromptAction {
+        if start >= end {
+            return PromptAction::Ignored;
+        }
+
+        self.chars.drain(start..end);
+        self.cursor = start;
+        PromptAction::Edited
+    }
+
+    fn move_to(&mut self, position: usize) -> PromptAction {
+        if position == self.cursor {
+            return PromptAction::Ignored;
+        }
+
+        self.cursor = position;
+        PromptAction::Moved
+    }
+
+    /// Inicio de la palabra anterior al cursor
+    fn word_start(&self) -> usize {
+        let mut idx = self.cursor;
+        while idx > 0 && !self.chars[idx - 1].is_alphanumeric() {
+            idx -= 1;
+        }
+        while idx > 0 && self.chars[idx - 1].is_alphanumeric() {
+            idx -= 1;
+        }
+        idx
+    }
+
+    /// Fin de la palabra siguiente al cursor
+    fn word_end(&self) -> usize {
+        let mut idx = self.cursor;
+        while idx < self.chars.len() && !self.chars[idx].is_alphanumeric() {
+            idx += 1;
+        }
+        while idx < self.chars.len() && self.chars[idx].is_alphanumeric() {
+            idx += 1;
+        }
+        idx
+    }
+}
+
+/// Recorrido del historial de un prompt con las flechas arriba/abajo
+///
+/// Al subir desde el texto que se está escribiendo, éste se guarda como
+/// borrador y se recupera al bajar más allá de la entrada más reciente.
+pub struct HistoryBrowser<'a> {
+    /// Entradas, de la más vieja a la más reciente
+    entries: &'a [String],
+
+    /// Entrada mostrada; `None` mientras se edita el borrador
+    position: Option<usize>,
+
+    draft: String,
+}
+
+impl<'a> HistoryBrowser<'a> {
+    pub fn new(entries: &'a [String]) -> Self {
+        HistoryBrowser {
+            entries,
+            position: None,
+            draft: String::new(),
+        }
+    }
+
+    /// Retrocede a la entrada anterior
+    ///
+    /// # Argumentos
+    /// * `current` - Texto actual del prompt, que se guarda como borrador
+    ///
+    /// # Retorna
+    /// El texto a mostrar, o `None` si no hay entradas más viejas
+    pub fn older(&mut self, current: &str) -> Option<&'a str> {
+        let previous = match self.position {
+            None => self.entries.len().checked_sub(1)?,
+            Some(0) => return None,
+            Some(idx) => idx - 1,
+        };
+
+        if self.position.is_none() {
+            self.draft = current.to_string();
+        }
+        self.position = Some(previous);
+        Some(&self.entries[previous])
+    }
+
+    /// Avanza a la entrada siguiente o, después de la última, al borrador
+    ///
+    /// # Retorna
+    /// El texto a mostrar, o `None` si ya se está en el borrador
+    pub fn newer(&mut self) -> Option<String> {
+        let idx = self.position?;
+        if idx + 1 < self.entries.len() {
+            self.position = Some(idx + 1);
+            Some(self.entries[idx + 1].clone())
+        } else {
+            self.position = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+}
+
+/// Dibuja el prompt y el texto en `row`, con el cursor en su posición
+///
+/// # Argumentos
+/// * `stdout` - Terminal donde escribir
+/// * `row` - Fila de la terminal
+/// * `width` - Ancho de la terminal en columnas
+/// * `prompt` - Mensaje que precede al texto
+/// * `editor` - Texto y cursor a mostrar
+pub fn render<W: Write>(stdout: &mut W, row: u16, width: usize, prompt: &str, editor: &LineEditor) {
+    let prompt_width = ui::display_width(prompt);
+    let available = width.saturating_sub(prompt_width).max(1);
+    let (start, end, cursor_col) = visible_window(&editor.chars, editor.cursor, available);
+    let visible: String = editor.chars[start..end].iter().collect();
+
+    ui::render_message(stdout, row, &format!("{}{}", prompt, visible));
+    write!(
+        stdout,
+        "{}{}",
+        cursor::MoveTo(
+            (prompt_width + cursor_col).min(width.saturating_sub(1)) as u16,
+            row
+        ),
+        cursor::Show
+    )
+    .unwrap();
+    stdout.flush().unwrap();
+}
+
+/// Calcula qué parte del texto cabe en `available` columnas
+///
+/// # Retorna
+/// `(inicio, fin, columna_del_cursor)`: el rango de caracteres visible y la
+/// columna del cursor relativa al inicio del texto visible
+fn visible_window(chars: &[char], cursor: usize, available: usize) -> (usize, usize, usize) {
+    let width_of = |c: &char| c.width().unwrap_or(0);
+
+    // Retroceder desde el cursor mientras quepa, reservando una columna para él
+    let mut start = cursor;
+    let mut used = 0;
+    while start > 0 {
+        let w = width_of(&chars[start - 1]);
+        if used + w >= available {
+            break;
+        }
+        used += w;
+        start -= 1;
+    }
+
+    let cursor_col = used;
+    let mut end = cursor;
+    while end < chars.len() {
+        let w = width_of(&chars[end]);
+        if used + w > available {
+            break;
+        }
+        used += w;
+        end += 1;
+    }
+
+    (start, end, cursor_col)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    #[test]
+    fn edits_in_the_middle_of_the_text() {
+        let mut editor = LineEditor::new("hola");
+        editor.handle_key(&key(KeyCode::Home));
+        editor.handle_key(&key(KeyCode::Right));
+        editor.handle_key(&key(KeyCode::Char('X')));
+        editor.handle_key(&key(KeyCode::Delete));
+
+        assert_eq!(editor.text(), "hXla");
+        assert_eq!(editor.cursor, 2);
+    }
+
+    #[test]
+    fn control_shortcuts_delete_words_and_ranges() {
+        let mut editor = LineEditor::new("ir a linea 42");
+        assert_eq!(editor.handle_key(&ctrl('w')), PromptAction::Edited);
+        assert_eq!(editor.text(), "ir a linea ");
+
+        editor.handle_key(&ctrl('a'));
+        editor.handle_key(&KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL));
+        editor.handle_key(&ctrl('k'));
+        assert_eq!(editor.text(), "ir");
+
+        editor.handle_key(&ctrl('u'));
+        assert_eq!(editor.text(), "");
+        assert_eq!(editor.handle_key(&ctrl('u')), PromptAction::Ignored);
+    }
+
+    #[test]
+    fn enter_and_esc_finish_the_prompt() {
+        let mut editor = LineEditor::new("");
+        assert_eq!(
+            editor.handle_key(&key(KeyCode::Enter)),
+            PromptAction::Submit
+        );
+        assert_eq!(editor.handle_key(&key(KeyCode::Esc)), PromptAction::Cancel);
+    }
+
+    #[test]
+    fn pasted_newlines_are_flattened() {
+        let mut editor = LineEditor::new("");
+        editor.insert_str("uno\r\ndos\n");
+
+        assert_eq!(editor.text(), "uno dos");
+    }
+
+    #[test]
+    fn visible_window_scrolls_to_cursor() {
+        let chars: Vec<char> = "abcdefghij".chars().collect();
+
+        assert_eq!(visible_window(&chars, 0, 4), (0, 4, 0));
+        assert_eq!(visible_window(&chars, 10, 4), (7, 10, 3));
+    }
+
+    #[test]
+    fn visible_window_counts_wide_characters() {
+        let chars: Vec<char> = "日本語".chars().collect();
+
+        assert_eq!(visible_window(&chars, 3, 10), (0, 3, 6));
+        assert_eq!(visible_window(&chars, 3, 4), (2, 3, 2));
+    }
+
+    #[test]
+    fn set_text_replaces_the_content() {
+        let mut editor = LineEditor::new("viejo");
+        editor.set_text("nuevo texto");
+
+        assert_eq!(editor.text(), "nuevo texto");
+        assert_eq!(editor.cursor, 11);
+    }
+
+    #[test]
+    fn history_browser_walks_entries_and_restores_draft() {
+        let entries = vec!["uno".to_string(), "dos".to_string()];
+        let mut browser = HistoryBrowser::new(&entries);
+
+        assert_eq!(browser.newer(), None);
+        assert_eq!(browser.older("borrador"), Some("dos"));
+        assert_eq!(browser.older("dos"), Some("uno"));
+        assert_eq!(browser.older("uno"), None);
+        assert_eq!(browser.newer().as_deref(), Some("dos"));
+        assert_eq!(browser.newer().as_deref(), Some("borrador"));
+        assert_eq!(browser.newer(), None);
+    }
+
+    #[test]
+    fn history_browser_without_entries_does_nothing() {
+        let mut browser = HistoryBrowser::new(&[]);
+
+        assert_eq!(browser.older("texto"), None);
+        assert_eq!(browser.newer(), None);
+    }
+}
@@ -1,3 +1,125 @@
+use regex::{Regex, RegexBuilder};
+
+/// Prefijo que activa el modo de expresión regular en el prompt de búsqueda
+pub const REGEX_PREFIX: &str = "re:";
+
+/// Prefijo que ignora mayúsculas/minúsculas
+pub const IGNORE_CASE_PREFIX: &str = "i:";
+
+/// Prefijo que activa smart-case: ignora mayúsculas salvo que la consulta las tenga
+pub const SMART_CASE_PREFIX: &str = "s:";
+
+/// Prefijo que limita las coincidencias a palabras completas
+pub const WHOLE_WORD_PREFIX: &str = "w:";
+
+/// Escapa un prefijo para buscarlo como texto (`\\s:foo` busca `s:foo`)
+pub const PREFIX_ESCAPE: char = '\\';
+
+const PREFIXES: [&str; 4] = [
+    REGEX_PREFIX,
+    IGNORE_CASE_PREFIX,
+    SMART_CASE_PREFIX,
+    WHOLE_WORD_PREFIX,
+];
+
+/// Modo de interpretación de la consulta
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Coincidencia exacta de texto
+    #[default]
+    Literal,
+
+    /// Expresión regular (sintaxis del crate `regex`)
+    Regex,
+}
+
+/// Sensibilidad a mayúsculas/minúsculas de la búsqueda
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CaseMode {
+    #[default]
+    Sensitive,
+    Insensitive,
+
+    /// Ignora mayúsculas salvo que la consulta contenga alguna
+    Smart,
+}
+
+/// Opciones que modifican cómo se interpreta la consulta
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    pub mode: SearchMode,
+    pub case: CaseMode,
+    pub whole_word: bool,
+}
+
+impl SearchOptions {
+    /// Determina si la búsqueda de `query` debe ignorar mayúsculas
+    fn ignores_case(&self, query: &str) -> bool {
+        match self.case {
+            CaseMode::Sensitive => false,
+            CaseMode::Insensitive => true,
+            CaseMode::Smart => !has_uppercase(query, self.mode == SearchMode::Regex),
+        }
+    }
+
+    /// Descripción corta de las opciones activas para la barra de mensajes
+    ///
+    /// # Retorna
+    /// Texto vacío si se usan las opciones por defecto
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+
+        if self.mode == SearchMode::Regex {
+            parts.push("regex");
+        }
+        match self.case {
+            CaseMode::Sensitive => {}
+            CaseMode::Insensitive => parts.push("ignora mayúsculas"),
+            CaseMode::Smart => parts.push("smart-case"),
+        }
+        if self.whole_word {
+            parts.push("palabra completa");
+        }
+
+        parts.join(", ")
+    }
+}
+
+/// Separa las opciones de búsqueda del texto ingresado en el prompt
+///
+/// La consulta puede empezar con uno o más prefijos, en cualquier orden:
+/// `re:` (expresión regular), `i:` (ignorar mayúsculas), `s:` (smart-case)
+/// y `w:` (palabra completa). Por ejemplo `i:w:id`. Una `\\` delante de
+/// un prefijo termina los prefijos y lo deja como parte del texto:
+/// `i:\\re:x` busca `re:x` sin distinguir mayúsculas. Delante de cualquier
+/// otra cosa la `\\` se conserva (`re:\\bid` sigue siendo una regex).
+pub fn parse_query(input: &str) -> (SearchOptions, &str) {
+    let mut options = SearchOptions::default();
+    let mut rest = input;
+
+    loop {
+        if let Some(tail) = rest.strip_prefix(PREFIX_ESCAPE)
+            && PREFIXES.iter().any(|prefix| tail.starts_with(prefix))
+        {
+            return (options, tail);
+        } else if let Some(tail) = rest.strip_prefix(REGEX_PREFIX) {
+            options.mode = SearchMode::Regex;
+            rest = tail;
+        } else if let Some(tail) = rest.strip_prefix(IGNORE_CASE_PREFIX) {
+            options.case = CaseMode::Insensitive;
+            rest = tail;
+        } else if let Some(tail) = rest.strip_prefix(SMART_CASE_PREFIX) {
+            options.case = CaseMode::Smart;
+            rest = tail;
+        } else if let Some(tail) = rest.strip_prefix(WHOLE_WORD_PREFIX) {
+            options.whole_word = true;
+            rest = tail;
+        } else {
+            return (options, rest);
+        }
+    }
+}
+
 /// Representa coincidencia de busqueda en el documento
 #[derive(Clone, Debug)]
 pub struct Match {
@@ -9,6 +131,10 @@ pub struct Match {
 
     /// Columna donde termina la coincidencia
     pub end_col: usize,
+
+    /// Texto de reemplazo calculado sobre el texto original de la línea
+    /// (ver `SearchState::prepare_replacement`)
+    pub replacement: Option<String>,
 }
 
 impl Match {
@@ -17,6 +143,7 @@ impl Match {
             line,
             start_col,
             end_col,
+            replacement: None,
         }
     }
 }
@@ -26,6 +153,12 @@ pub struct SearchState {
     query: Option<String>,
     matches: Vec<Match>,
     current_index: Option<usize>,
+    options: SearchOptions,
+    regex: Option<Regex>,
+    error: Option<String>,
+
+    /// Reemplazo con el que se calcularon los textos de las coincidencias
+    prepared_replacement: Option<String>,
 }
 
 impl SearchState {
@@ -34,9 +167,28 @@ impl SearchState {
             query: None,
             matches: Vec::new(),
             current_index: None,
+            options: SearchOptions::default(),
+            regex: None,
+            error: None,
+            prepared_replacement: None,
         }
     }
 
+    /// Cambia las opciones usadas por las siguientes búsquedas
+    pub fn set_options(&mut self, options: SearchOptions) {
+        self.options = options;
+    }
+
+    /// Obtener las opciones de búsqueda actuales
+    pub fn options(&self) -> SearchOptions {
+        self.options
+    }
+
+    /// Obtener el error de la última búsqueda (por ejemplo, una regex inválida)
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
     /// Obtener la consulta actual
     pub fn query(&self) -> Option<&String> {
         self.query.as_ref()
@@ -79,6 +231,9 @@ impl SearchState {
         // Limpiar estado anterior
         self.matches.clear();
         self.current_index = None;
+        self.regex = None;
+        self.error = None;
+        self.prepared_replacement = None;
 
         // Si la búsqueda está vacía, salir
         if query.is_empty() {
@@ -86,27 +241,207 @@ impl SearchState {
             return 0;
         }
 
+        let ignore_case = self.options.ignores_case(query);
+
+        // Palabra completa: se delimita el patrón con `\b` en lugar de filtrar
+        // después, así no se pierden coincidencias que `find_iter` descartó
+        // por superponerse con otras que no eran palabras completas
+        let pattern = match (self.options.mode, self.options.whole_word) {
+            (SearchMode::Literal, false) => None,
+            (SearchMode::Literal, true) => Some(whole_word_literal(query)),
+            (SearchMode::Regex, false) => Some(query.to_string()),
+            (SearchMode::Regex, true) => Some(format!(r"\b(?:{})\b", query)),
+        };
+
+        match pattern {
+            None => self.search_literal(query, lines, ignore_case),
+            Some(pattern) => match RegexBuilder::new(&pattern)
+                .case_insensitive(ignore_case)
+                .build()
+            {
+                Ok(regex) => {
+                    self.search_regex(&regex, lines);
+                    self.regex = Some(regex);
+                }
+                Err(e) => {
+                    self.query = None;
+                    self.error = Some(regex_error_summary(&e));
+                    return 0;
+                }
+            },
+        }
+
         // Guardar la consulta
         self.query = Some(query.to_string());
-        let query_len = query.chars().count();
+
+        // Si hay coincidencias, posicionarse en la primera
+        if !self.matches.is_empty() {
+            self.current_index = Some(0);
+        }
+
+        self.matches.len()
+    }
+
+    fn search_literal(&mut self, query: &str, lines: &[String], ignore_case: bool) {
+        let query: Vec<char> = query.chars().collect();
+        let query_len = query.len();
 
         // Buscar en todas las líneas
         for (line_idx, line) in lines.iter().enumerate() {
+            let chars: Vec<char> = line.chars().collect();
+
             // Buscar todas las ocurrencias en esta línea usando índices por carácter
-            for (char_col, (byte_idx, _)) in line.char_indices().enumerate() {
-                if line[byte_idx..].starts_with(query) {
-                    self.matches
-                        .push(Match::new(line_idx, char_col, char_col + query_len));
+            for start_col in 0..(chars.len() + 1).saturating_sub(query_len) {
+                let end_col = start_col + query_len;
+                let found = chars[start_col..end_col]
+                    .iter()
+                    .zip(&query)
+                    .all(|(a, b)| chars_equal(*a, *b, ignore_case));
+
+                if found {
+                    self.matches.push(Match::new(line_idx, start_col, end_col));
                 }
             }
         }
+    }
 
-        // Si hay coincidencias, posicionarse en la primera
-        if !self.matches.is_empty() {
-            self.current_index = Some(0);
+    fn search_regex(&mut self, regex: &Regex, lines: &[String]) {
+        for (line_idx, line) in lines.iter().enumerate() {
+            for found in regex.find_iter(line) {
+                // Las coincidencias vacías (ej. `a*`) no se pueden resaltar ni recorrer
+                if found.is_empty() {
+                    continue;
+                }
+
+                let start_col = byte_to_char_col(line, found.start());
+                let end_col = start_col + found.as_str().chars().count();
+                self.matches.push(Match::new(line_idx, start_col, end_col));
+            }
         }
+    }
 
-        self.matches.len()
+    /// Indica si los textos de reemplazo ya se calcularon para `replacement`
+    pub fn is_prepared(&self, replacement: &str) -> bool {
+        self.prepared_replacement.as_deref() == Some(replacement)
+    }
+
+    /// Calcula el texto de reemplazo de cada coincidencia
+    ///
+    /// Se hace sobre las mismas líneas en las que se buscó y antes de
+    /// modificar nada: al reemplazar una coincidencia cambia el contexto de
+    /// las siguientes (por ejemplo `\b`), así que no se pueden volver a
+    /// evaluar sobre el texto ya modificado. En modo regex se sustituyen los
+    /// grupos de captura (`$1`, `${nombre}`); en modo literal el reemplazo se
+    /// usa tal cual. Si ya se calcularon con el mismo reemplazo no hace nada.
+    ///
+    /// # Argumentos
+    /// * `replacement` - Texto de reemplazo
+    /// * `lines` - Líneas del documento, sin modificar desde la búsqueda
+    ///
+    /// # Retorna
+    /// Un mensaje de error si alguna coincidencia no se pudo volver a encontrar
+    pub fn prepare_replacement(
+        &mut self,
+        replacement: &str,
+        lines: &[String],
+    ) -> Result<(), String> {
+        if self.is_prepared(replacement) {
+            return Ok(());
+        }
+
+        // En modo literal con palabra completa también hay regex, pero el
+        // reemplazo no tiene grupos de captura
+        let regex = self
+            .regex
+            .as_ref()
+            .filter(|_| self.options.mode == SearchMode::Regex);
+        for m in &mut self.matches {
+            let text = match regex {
+                None => replacement.to_string(),
+                Some(regex) => lines
+                    .get(m.line)
+                    .and_then(|line| expand_captures(regex, line, m, replacement))
+                    .ok_or_else(|| {
+                        format!(
+                            "No se pudo expandir el reemplazo en la línea {}",
+                            m.line + 1
+                        )
+                    })?,
+            };
+            m.replacement = Some(text);
+        }
+
+        self.prepared_replacement = Some(replacement.to_string());
+        Ok(())
+    }
+
+    /// Selecciona la primera coincidencia en o después de una posición
+    ///
+    /// Si no hay ninguna después de la posición, vuelve a la primera del
+    /// documento (búsqueda circular).
+    pub fn select_nearest(&mut self, line: usize, col: usize) {
+        if self.matches.is_empty() {
+            return;
+        }
+
+        let idx = self
+            .matches
+            .iter()
+            .position(|m| (m.line, m.start_col) >= (line, col))
+            .unwrap_or(0);
+        self.current_index = Some(idx);
+    }
+
+    /// Actualiza las coincidencias después de reemplazar la coincidencia actual
+    ///
+    /// Elimina la coincidencia reemplazada y las que se superponían con ella,
+    /// y desplaza las posteriores de la misma línea según la diferencia de
+    /// longitud. La coincidencia actual pasa a ser la siguiente.
+    ///
+    /// # Argumentos
+    /// * `replacement_len` - Longitud en caracteres del texto insertado
+    pub fn apply_replacement(&mut self, replacement_len: usize) {
+        let Some(current_idx) = self.current_index else {
+            return;
+        };
+
+        let replaced = self.matches[current_idx].clone();
+        let mut next_idx = current_idx;
+        let mut updated = Vec::with_capacity(self.matches.len());
+
+        for (idx, m) in self.matches.drain(..).enumerate() {
+            if m.line != replaced.line {
+                updated.push(m);
+                continue;
+            }
+
+            let overlaps = m.start_col < replaced.end_col && m.end_col > replaced.start_col;
+            if idx == current_idx || overlaps {
+                if idx < current_idx {
+                    next_idx -= 1;
+                }
+                continue;
+            }
+
+            if m.start_col >= replaced.end_col {
+                let start_col =
+                    m.start_col - replaced.end_col + replaced.start_col + replacement_len;
+                updated.push(Match {
+                    start_col,
+                    end_col: start_col + m.end_col - m.start_col,
+                    ..m
+                });
+            } else {
+                updated.push(m);
+            }
+        }
+
+        self.matches = updated;
+        self.current_index = if self.matches.is_empty() {
+            None
+        } else {
+            Some(next_idx % self.matches.len())
+        };
     }
 
     /// Avanza a la siguiente coincidencia (circular)
@@ -148,6 +483,98 @@ impl SearchState {
     }
 }
 
+/// Compara dos caracteres, opcionalmente sin distinguir mayúsculas
+fn chars_equal(a: char, b: char, ignore_case: bool) -> bool {
+    a == b || (ignore_case && a.to_lowercase().eq(b.to_lowercase()))
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// Patrón para buscar `query` literal como palabra completa
+///
+/// `\b` solo se agrega en los extremos que son caracteres de palabra: en
+/// `-x` el guion no tiene que estar al inicio de una palabra.
+fn whole_word_literal(query: &str) -> String {
+    let starts_word = query.chars().next().is_some_and(is_word_char);
+    let ends_word = query.chars().last().is_some_and(is_word_char);
+
+    format!(
+        "{}{}{}",
+        if starts_word { r"\b" } else { "" },
+        regex::escape(query),
+        if ends_word { r"\b" } else { "" }
+    )
+}
+
+/// Indica si la consulta contiene mayúsculas (para smart-case)
+///
+/// En una regex se ignoran los caracteres escapados, como `\W` o `\S`.
+fn has_uppercase(query: &str, is_regex: bool) -> bool {
+    let mut escaped = false;
+
+    for ch in query.chars() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        if is_regex && ch == '\\' {
+            escaped = true;
+            continue;
+        }
+        if ch.is_uppercase() {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Convierte un índice de byte de una línea en columna de carácter
+fn byte_to_char_col(line: &str, byte_idx: usize) -> usize {
+    line[..byte_idx].chars().count()
+}
+
+/// Convierte una columna de carácter en índice de byte de la línea
+fn char_to_byte_idx(line: &str, col: usize) -> usize {
+    line.char_indices()
+        .nth(col)
+        .map(|(idx, _)| idx)
+        .unwrap_or(line.len())
+}
+
+/// Expande los grupos de captura de `replacement` para la coincidencia `m`
+///
+/// # Retorna
+/// `None` si la regex ya no encuentra exactamente esa coincidencia en `line`
+fn expand_captures(regex: &Regex, line: &str, m: &Match, replacement: &str) -> Option<String> {
+    let start = char_to_byte_idx(line, m.start_col);
+    let end = char_to_byte_idx(line, m.end_col);
+    let captures = regex.captures_at(line, start)?;
+    let whole = captures.get(0)?;
+    if whole.start() != start || whole.end() != end {
+        return None;
+    }
+
+    let mut expanded = String::new();
+    captures.expand(replacement, &mut expanded);
+    Some(expanded)
+}
+
+/// Resume el error de compilación de una regex en una sola línea
+fn regex_error_summary(error: &regex::Error) -> String {
+    let message = error.to_string();
+    message
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or(&message)
+        .trim()
+        .trim_start_matches("error: ")
+        .to_string()
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -199,4 +626,267 @@ mod tests {
         assert_eq!(state.matches[1].start_col, 1);
         assert_eq!(state.matches[1].end_col, 3);
     }
+
+    #[test]
+    fn test_parse_query_detects_regex_prefix() {
+        let (options, query) = parse_query("re:fo+");
+        assert_eq!(options.mode, SearchMode::Regex);
+        assert_eq!(query, "fo+");
+
+        let (options, query) = parse_query("foo");
+        assert_eq!(options, SearchOptions::default());
+        assert_eq!(query, "foo");
+    }
+
+    #[test]
+    fn test_parse_query_combines_prefixes() {
+        let (options, query) = parse_query("w:i:re:id");
+
+        assert_eq!(options.mode, SearchMode::Regex);
+        assert_eq!(options.case, CaseMode::Insensitive);
+        assert!(options.whole_word);
+        assert_eq!(query, "id");
+        assert_eq!(
+            options.label(),
+            "regex, ignora mayúsculas, palabra completa"
+        );
+    }
+
+    #[test]
+    fn test_parse_query_backslash_ends_prefixes() {
+        let (options, query) = parse_query("\\s:foo");
+        assert_eq!(options, SearchOptions::default());
+        assert_eq!(query, "s:foo");
+
+        let (options, query) = parse_query("i:\\re:x");
+        assert_eq!(options.case, CaseMode::Insensitive);
+        assert_eq!(options.mode, SearchMode::Literal);
+        assert_eq!(query, "re:x");
+
+        let (options, query) = parse_query("re:\\bid");
+        assert_eq!(options.mode, SearchMode::Regex);
+        assert_eq!(query, "\\bid");
+    }
+
+    #[test]
+    fn test_whole_word_regex_keeps_matches_skipped_as_overlaps() {
+        // `find_iter` sin `\b` toma `ab` dentro de `xab` y se saltea `ab c`
+        let lines = vec!["xab c".to_string(), "ab c".to_string()];
+        let mut state = SearchState::new();
+        state.set_options(SearchOptions {
+            mode: SearchMode::Regex,
+            whole_word: true,
+            ..SearchOptions::default()
+        });
+
+        assert_eq!(state.search("a?b( c)?|xab", &lines), 2);
+        assert_eq!(state.matches[0].start_col, 0);
+        assert_eq!(state.matches[0].end_col, 3);
+        assert_eq!(state.matches[1].end_col, 4);
+    }
+
+    #[test]
+    fn test_whole_word_literal_with_punctuation() {
+        let lines = vec!["a -x b-x".to_string()];
+        let mut state = SearchState::new();
+        state.set_options(SearchOptions {
+            whole_word: true,
+            ..SearchOptions::default()
+        });
+
+        assert_eq!(state.search("-x", &lines), 2);
+        assert_eq!(state.search("x", &lines), 2);
+    }
+
+    #[test]
+    fn test_case_insensitive_search() {
+        let lines = vec!["Hola HOLA hola".to_string()];
+        let mut state = SearchState::new();
+        state.set_options(SearchOptions {
+            case: CaseMode::Insensitive,
+            ..SearchOptions::default()
+        });
+
+        assert_eq!(state.search("hola", &lines), 3);
+    }
+
+    #[test]
+    fn test_smart_case_search() {
+        let lines = vec!["Hola hola".to_string()];
+        let mut state = SearchState::new();
+        state.set_options(SearchOptions {
+            case: CaseMode::Smart,
+            ..SearchOptions::default()
+        });
+
+        assert_eq!(state.search("hola", &lines), 2);
+        assert_eq!(state.search("Hola", &lines), 1);
+    }
+
+    #[test]
+    fn test_smart_case_ignores_regex_escapes() {
+        let lines = vec!["ID id".to_string()];
+        let mut state = SearchState::new();
+        state.set_options(SearchOptions {
+            mode: SearchMode::Regex,
+            case: CaseMode::Smart,
+            whole_word: false,
+        });
+
+        assert_eq!(state.search(r"\Sd", &lines), 2);
+    }
+
+    #[test]
+    fn test_whole_word_search_skips_identifiers() {
+        let lines = vec!["let id = idx + id_2 + id;".to_string()];
+        let mut state = SearchState::new();
+        state.set_options(SearchOptions {
+            whole_word: true,
+            ..SearchOptions::default()
+        });
+
+        let count = state.search("id", &lines);
+
+        assert_eq!(count, 2);
+        assert_eq!(state.matches[0].start_col, 4);
+        assert_eq!(state.matches[1].start_col, 22);
+    }
+
+    #[test]
+    fn test_case_insensitive_utf8_overlapping() {
+        let lines = vec!["ááÁ".to_string()];
+        let mut state = SearchState::new();
+        state.set_options(SearchOptions {
+            case: CaseMode::Insensitive,
+            ..SearchOptions::default()
+        });
+
+        let count = state.search("ÁÁ", &lines);
+
+        assert_eq!(count, 2);
+        assert_eq!(state.matches[0].start_col, 0);
+        assert_eq!(state.matches[0].end_col, 2);
+        assert_eq!(state.matches[1].start_col, 1);
+        assert_eq!(state.matches[1].end_col, 3);
+    }
+
+    #[test]
+    fn test_regex_search_reports_char_columns() {
+        let lines = vec!["ñandú 42 y 7".to_string()];
+        let mut state = SearchState::new();
+        state.set_options(SearchOptions {
+            mode: SearchMode::Regex,
+            ..SearchOptions::default()
+        });
+
+        let count = state.search(r"\d+", &lines);
+
+        assert_eq!(count, 2);
+        assert_eq!(state.matches[0].start_col, 6);
+        assert_eq!(state.matches[0].end_col, 8);
+        assert_eq!(state.matches[1].start_col, 11);
+        assert_eq!(state.matches[1].end_col, 12);
+    }
+
+    #[test]
+    fn test_invalid_regex_sets_error() {
+        let lines = vec!["abc".to_string()];
+        let mut state = SearchState::new();
+        state.set_options(SearchOptions {
+            mode: SearchMode::Regex,
+            ..SearchOptions::default()
+        });
+
+        let count = state.search("(abc", &lines);
+
+        assert_eq!(count, 0);
+        assert!(!state.is_active());
+        assert!(state.error().is_some());
+    }
+
+    #[test]
+    fn test_regex_replacement_expands_captures() {
+        let lines = vec!["año=2024".to_string()];
+        let mut state = SearchState::new();
+        state.set_options(SearchOptions {
+            mode: SearchMode::Regex,
+            ..SearchOptions::default()
+        });
+        state.search(r"(\w+)=(\d+)", &lines);
+
+        state.prepare_replacement("$2:$1", &lines).unwrap();
+
+        assert_eq!(
+            state.current_match().unwrap().replacement.as_deref(),
+            Some("2024:año")
+        );
+    }
+
+    #[test]
+    fn test_replacements_are_expanded_on_the_original_line() {
+        let lines = vec!["xaa".to_string()];
+        let mut state = SearchState::new();
+        state.set_options(SearchOptions {
+            mode: SearchMode::Regex,
+            ..SearchOptions::default()
+        });
+        state.search(r"\B(a)", &lines);
+        state.prepare_replacement("<$1>", &lines).unwrap();
+
+        let texts: Vec<_> = state
+            .matches()
+            .iter()
+            .map(|m| m.replacement.as_deref())
+            .collect();
+        assert_eq!(texts, [Some("<a>"), Some("<a>")]);
+
+        // Sobre un texto distinto al de la búsqueda es un error, no un reemplazo vacío
+        state.search(r"\B(a)", &lines);
+        assert!(
+            state
+                .prepare_replacement("$1", &["x a".to_string()])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_apply_replacement_shifts_following_matches() {
+        let lines = vec!["a a".to_string(), "a".to_string()];
+        let mut state = SearchState::new();
+        state.search("a", &lines);
+
+        state.apply_replacement(3);
+
+        assert_eq!(state.match_count(), 2);
+        assert_eq!(state.current_index(), Some(0));
+        assert_eq!(state.matches[0].start_col, 4);
+        assert_eq!(state.matches[0].end_col, 5);
+        assert_eq!(state.matches[1].line, 1);
+        assert_eq!(state.matches[1].start_col, 0);
+    }
+
+    #[test]
+    fn test_apply_replacement_drops_overlapping_matches() {
+        let lines = vec!["ááá".to_string()];
+        let mut state = SearchState::new();
+        state.search("áá", &lines);
+
+        state.apply_replacement(1);
+
+        assert_eq!(state.match_count(), 0);
+        assert_eq!(state.current_index(), None);
+    }
+
+    #[test]
+    fn test_select_nearest_wraps_around() {
+        let lines = vec!["a b a".to_string(), "a".to_string()];
+        let mut state = SearchState::new();
+        state.search("a", &lines);
+
+        state.select_nearest(0, 1);
+        assert_eq!(state.current_index(), Some(1));
+
+        state.select_nearest(1, 1);
+        assert_eq!(state.current_index(), Some(0));
+    }
 }
@@ -0,0 +1,184 @@
+//! Archivos de recuperación (swap)
+//!
+//! Mientras hay cambios sin guardar, el editor escribe periódicamente el
+//! contenido del buffer y la posición del cursor en `.archivo.hy-swp`, en el
+//! mismo directorio del archivo. Si el editor termina de forma inesperada,
+//! al volver a abrir el archivo se ofrece recuperar esos cambios.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::file_io;
+
+/// Tiempo mínimo entre dos escrituras del archivo de recuperación
+pub const SWAP_INTERVAL: Duration = Duration::from_secs(4);
+
+/// Primera línea de todo archivo de recuperación
+const HEADER: &str = "HYPERION-SWAP 1";
+
+/// Cantidad máxima de celdas de la tabla LCS al calcular diferencias
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Contenido de un archivo de recuperación
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SwapData {
+    /// Posición (línea, columna) del cursor
+    pub cursor: (usize, usize),
+
+    /// Texto del buffer, con `\n` como fin de línea
+    pub text: String,
+}
+
+/// Ruta del archivo de recuperación de `path` (`dir/.nombre.hy-swp`)
+pub fn swap_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    path.with_file_name(format!(".{}.hy-swp", name))
+}
+
+/// Escribe el archivo de recuperación de forma atómica
+pub fn write(path: &Path, data: &SwapData) -> io::Result<()> {
+    file_io::write_atomic(path, serialize(data).as_bytes(), false)
+}
+
+/// Lee un archivo de recuperación
+///
+/// # Retorna
+/// Los datos guardados, o un error `InvalidData` si el archivo no tiene el formato esperado
+pub fn read(path: &Path) -> io::Result<SwapData> {
+    let content = fs::read_to_string(path)?;
+    parse(&content).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "el archivo de recuperación está dañado",
+        )
+    })
+}
+
+/// Elimina el archivo de recuperación, ignorando si no existía
+pub fn remove(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn serialize(data: &SwapData) -> String {
+    format!(
+        "{}\ncursor {} {}\n{}",
+        HEADER, data.cursor.0, data.cursor.1, data.text
+    )
+}
+
+fn parse(content: &str) -> Option<SwapData> {
+    let rest = content.strip_prefix(HEADER)?.strip_prefix('\n')?;
+    let (cursor_line, text) = rest.split_once('\n')?;
+
+    let mut parts = cursor_line.strip_prefix("cursor ")?.split(' ');
+    let line = parts.next()?.parse().ok()?;
+    let col = parts.next()?.parse().ok()?;
+
+    Some(SwapData {
+        cursor: (line, col),
+        text: text.to_string(),
+    })
+}
+
+/// Diferencias línea a línea entre dos textos
+///
+/// # Retorna
+/// Las líneas distintas, con prefijo `- ` (solo en `old`) o `+ ` (solo en `new`)
+pub fn line_diff(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    if old.len() * new.len() > MAX_DIFF_CELLS {
+        return old
+            .iter()
+            .map(|line| format!("- {}", line))
+            .chain(new.iter().map(|line| format!("+ {}", line)))
+            .collect();
+    }
+
+    // lcs[i][j] = largo de la subsecuencia común más larga de old[i..] y new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            diff.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swap_path_is_hidden_next_to_file() {
+        assert_eq!(
+            swap_path(Path::new("/tmp/notas.txt")),
+            PathBuf::from("/tmp/.notas.txt.hy-swp")
+        );
+        assert_eq!(
+            swap_path(Path::new("main.rs")),
+            PathBuf::from(".main.rs.hy-swp")
+        );
+    }
+
+    #[test]
+    fn serialize_round_trips() {
+        let data = SwapData {
+            cursor: (2, 5),
+            text: "uno\ndos\n\ntres\n".to_string(),
+        };
+
+        assert_eq!(parse(&serialize(&data)), Some(data));
+        assert_eq!(parse("otro formato"), None);
+    }
+
+    #[test]
+    fn line_diff_reports_changes() {
+        let diff = line_diff("a\nb\nc\n", "a\nx\nc\nd\n");
+
+        assert_eq!(diff, vec!["- b", "+ x", "+ d"]);
+        assert!(line_diff("igual\n", "igual\n").is_empty());
+    }
+}
@@ -1,10 +1,9 @@
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum SyntaxLanguage {
-    Rust,
-    Python,
-    JavaScript,
-    PlainText,
-}
+//! Tokenizador para el resaltado de sintaxis
+//!
+//! Las reglas de cada lenguaje (comentarios, strings, palabras clave...)
+//! vienen de su `LanguageDef`; ver el módulo `language`.
+
+use crate::language::LanguageDef;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TokenKind {
@@ -12,75 +11,233 @@ pub enum TokenKind {
     String,
     Number,
     Comment,
+
+    /// Nombres de tipos: identificadores en CamelCase y los tipos del lenguaje
+    Type,
+
+    /// Nombre de una función en su definición o en una llamada
+    Function,
+
+    /// Invocación de macro de Rust (`println!`)
+    Macro,
+
+    /// Atributo de Rust (`#[derive(Debug)]`) o decorador de Python (`@property`)
+    Attribute,
+
+    /// Lifetime de Rust (`'a`, `'static`)
+    Lifetime,
+
+    /// Literal de carácter de Rust (`'x'`, `'\n'`)
+    Char,
+
+    /// Operadores y signos de puntuación
+    Operator,
+
+    /// Título de Markdown o tabla de TOML (`[seccion]`)
+    Heading,
+
+    /// Clave de JSON, TOML o YAML
+    Key,
+
+    /// Variable de shell (`$HOME`, `${1}`)
+    Variable,
+
+    /// Texto con énfasis de Markdown (`*cursiva*`, `**negrita**`)
+    Emphasis,
+
+    /// Etiqueta de HTML (`<div`, `</p>`)
+    Tag,
 }
 
-pub fn detect_language(filename: Option<&str>) -> SyntaxLanguage {
-    let Some(filename) = filename else {
-        return SyntaxLanguage::PlainText;
-    };
+/// Estado del lexer al terminar una línea, que se arrastra a la siguiente
+///
+/// Permite resaltar comentarios de bloque y strings que ocupan varias líneas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineState {
+    #[default]
+    Normal,
 
-    let ext = std::path::Path::new(filename)
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|e| e.to_ascii_lowercase());
+    /// Dentro de un comentario de bloque; en Rust se pueden anidar
+    BlockComment { depth: u32 },
 
-    match ext.as_deref() {
-        Some("rs") => SyntaxLanguage::Rust,
-        Some("py") => SyntaxLanguage::Python,
-        Some("js") | Some("mjs") | Some("cjs") | Some("ts") => SyntaxLanguage::JavaScript,
-        _ => SyntaxLanguage::PlainText,
-    }
+    /// Dentro de un string que continúa en la línea siguiente
+    String { delimiter: StringDelimiter },
+
+    /// Dentro de una etiqueta de HTML cuyos atributos siguen en la línea siguiente
+    Tag,
 }
 
-pub fn tokenize_line(line: &str, language: SyntaxLanguage) -> Vec<Option<TokenKind>> {
+/// Delimitador que cierra un string
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringDelimiter {
+    /// Índice del delimitador en `LanguageDef::strings`
+    Rule(usize),
+
+    /// String crudo de Rust (`r#"..."#`), con la cantidad de `#`
+    Raw(usize),
+}
+
+/// Tokeniza una línea partiendo del estado en que terminó la anterior
+///
+/// # Argumentos
+/// * `line` - Texto de la línea
+/// * `language` - Reglas del lenguaje del archivo
+/// * `state` - Estado al inicio de la línea (ver `SyntaxCache`)
+///
+/// # Retorna
+/// El tipo de token de cada carácter y el estado al final de la línea
+pub fn tokenize_line(
+    line: &str,
+    language: &LanguageDef,
+    state: LineState,
+) -> (Vec<Option<TokenKind>>, LineState) {
     let chars: Vec<char> = line.chars().collect();
     let mut tokens = vec![None; chars.len()];
 
-    if language == SyntaxLanguage::PlainText {
-        return tokens;
-    }
-
+    // Entre `<nombre` y `>`: el estado `Tag` solo se usa entre líneas
+    let mut in_tag = state == LineState::Tag;
+    let mut state = if in_tag { LineState::Normal } else { state };
     let mut i = 0;
+    let indent = chars.iter().take_while(|c| c.is_whitespace()).count();
+
+    // El identificador anterior fue `fn`, `def` o `function`
+    let mut after_definition = false;
+
     while i < chars.len() {
-        if starts_comment(&chars, i, language) {
-            for t in &mut tokens[i..] {
-                *t = Some(TokenKind::Comment);
+        match state {
+            LineState::BlockComment { depth } => {
+                let (end, next) = scan_block_comment(&chars, i, depth, language);
+                mark(&mut tokens, i, end, TokenKind::Comment);
+                i = end;
+                state = next;
+                continue;
             }
-            break;
+            LineState::String { delimiter } => {
+                let (end, closed) = scan_string(&chars, i, delimiter, language);
+                mark(&mut tokens, i, end, TokenKind::String);
+                if interpolates(delimiter, language) {
+                    mark_variables(&mut tokens, &chars, i, end, language);
+                }
+                i = end;
+                if closed {
+                    state = LineState::Normal;
+                }
+                continue;
+            }
+            LineState::Normal | LineState::Tag => {}
         }
 
-        let ch = chars[i];
+        // Solo el identificador que sigue directamente a `fn` es su nombre:
+        // en `function (a)` el `(` indica que no lo tiene
+        if !chars[i].is_whitespace() && !is_ident_start(chars[i]) {
+            after_definition = false;
+        }
 
-        if ch == '"' || ch == '\'' {
-            let quote = ch;
-            let start = i;
-            i += 1;
-            while i < chars.len() {
-                if chars[i] == '\\' {
-                    i += 2;
-                    continue;
-                }
-                if chars[i] == quote {
-                    i += 1;
-                    break;
-                }
-                i += 1;
+        // Antes que los comentarios: en shell `$#` es una variable
+        if let Some(end) = variable_end(&chars, i, language) {
+            mark(&mut tokens, i, end, TokenKind::Variable);
+            i = end;
+            continue;
+        }
+
+        if let Some((open, _)) = &language.block_comment
+            && starts_with_at(&chars, i, open)
+        {
+            let end = i + open.chars().count();
+            mark(&mut tokens, i, end, TokenKind::Comment);
+            i = end;
+            state = LineState::BlockComment { depth: 1 };
+            continue;
+        }
+
+        // Va después del comentario de bloque porque en Lua `--[[` empieza con `--`
+        if language
+            .line_comments
+            .iter()
+            .any(|marker| starts_with_at(&chars, i, marker))
+        {
+            mark(&mut tokens, i, chars.len(), TokenKind::Comment);
+            break;
+        }
+
+        if i == indent {
+            if language
+                .headings
+                .iter()
+                .any(|m| starts_with_at(&chars, i, m))
+            {
+                // Hasta el final de la línea o el comentario (`[tabla] # ...`)
+                let end = (i + 1..chars.len())
+                    .find(|&j| {
+                        language
+                            .line_comments
+                            .iter()
+                            .any(|m| starts_with_at(&chars, j, m))
+                    })
+                    .unwrap_or(chars.len());
+                mark(&mut tokens, i, end, TokenKind::Heading);
+                i = end;
+                continue;
             }
-            for token in tokens.iter_mut().take(i.min(chars.len())).skip(start) {
-                *token = Some(TokenKind::String);
+
+            if let Some(end) = directive_end(&chars, i, language) {
+                mark(&mut tokens, i, end, TokenKind::Attribute);
+                i = end;
+                continue;
             }
+        }
+
+        if let Some(end) = key_end(&chars, i, language) {
+            mark(&mut tokens, i, end, TokenKind::Key);
+            i = end;
             continue;
         }
 
-        if ch.is_ascii_digit() {
-            let start = i;
+        if let Some((kind, end)) = quote_token(&chars, i, language) {
+            mark(&mut tokens, i, end, kind);
+            i = end;
+            continue;
+        }
+
+        if let Some((delimiter, open_len)) =
+            string_start(&chars, i, language).filter(|_| in_tag || !language.tags)
+        {
+            mark(&mut tokens, i, i + open_len, TokenKind::String);
+            i += open_len;
+            state = LineState::String { delimiter };
+            continue;
+        }
+
+        if let Some(end) = attribute_end(&chars, i, language) {
+            mark(&mut tokens, i, end, TokenKind::Attribute);
+            i = end;
+            continue;
+        }
+
+        if let Some(end) = tag_end(&chars, i, in_tag, language) {
+            mark(&mut tokens, i, end, TokenKind::Tag);
+            in_tag = chars[i] == '<';
+            i = end;
+            continue;
+        }
+
+        if let Some(end) = emphasis_end(&chars, i, language) {
+            mark(&mut tokens, i, end, TokenKind::Emphasis);
+            i = end;
+            continue;
+        }
+
+        if language.prose {
             i += 1;
-            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '_') {
-                i += 1;
-            }
-            for token in tokens.iter_mut().take(i).skip(start) {
-                *token = Some(TokenKind::Number);
-            }
+            continue;
+        }
+
+        let ch = chars[i];
+
+        if ch.is_ascii_digit() {
+            let end = number_end(&chars, i, language);
+            mark(&mut tokens, i, end, TokenKind::Number);
+            i = end;
             continue;
         }
 
@@ -91,135 +248,1075 @@ pub fn tokenize_line(line: &str, language: SyntaxLanguage) -> Vec<Option<TokenKi
                 i += 1;
             }
             let ident: String = chars[start..i].iter().collect();
-            if is_keyword(&ident, language) {
-                for token in tokens.iter_mut().take(i).skip(start) {
-                    *token = Some(TokenKind::Keyword);
-                }
+            let next = chars.get(i).copied();
+
+            let kind = if language.is_keyword(&ident) {
+                Some(TokenKind::Keyword)
+            } else if language.macros && next == Some('!') && chars.get(i + 1) != Some(&'=') {
+                i += 1;
+                Some(TokenKind::Macro)
+            } else if is_type_name(&ident, language) {
+                Some(TokenKind::Type)
+            } else if next == Some('(') || after_definition {
+                Some(TokenKind::Function)
+            } else {
+                None
+            };
+
+            if let Some(kind) = kind {
+                mark(&mut tokens, start, i, kind);
             }
+            after_definition = language.is_definition_keyword(&ident);
             continue;
         }
 
+        if is_operator(ch) {
+            mark(&mut tokens, i, i + 1, TokenKind::Operator);
+        }
         i += 1;
     }
 
-    tokens
+    // Los strings que no pueden ocupar varias líneas terminan con la línea
+    if let LineState::String {
+        delimiter: StringDelimiter::Rule(idx),
+    } = state
+        && !language.strings.get(idx).is_some_and(|rule| rule.multiline)
+    {
+        state = LineState::Normal;
+    }
+
+    if in_tag && state == LineState::Normal {
+        state = LineState::Tag;
+    }
+
+    (tokens, state)
+}
+
+/// Estados del lexer al inicio de cada línea de un buffer
+///
+/// Se calculan a demanda desde la primera línea y, al editar, se descartan
+/// desde la línea modificada hacia abajo con `invalidate_from`.
+#[derive(Clone, Debug, Default)]
+pub struct SyntaxCache {
+    /// Nombre del lenguaje con el que se calcularon los estados
+    language: Option<String>,
+
+    /// `states[i]` es el estado al comenzar la línea `i`
+    states: Vec<LineState>,
+}
+
+impl SyntaxCache {
+    /// Descarta los estados de las líneas posteriores a `line`
+    ///
+    /// El estado al inicio de `line` sigue siendo válido: solo depende de
+    /// las líneas anteriores.
+    pub fn invalidate_from(&mut self, line: usize) {
+        self.states.truncate(line + 1);
+    }
+
+    /// Estado al inicio de `line`, tokenizando las líneas anteriores que falten
+    ///
+    /// # Argumentos
+    /// * `line` - Índice de la línea
+    /// * `language` - Lenguaje del archivo; si cambia se descarta todo
+    /// * `line_text` - Obtiene el texto de una línea por su índice
+    pub fn state_at<F>(&mut self, line: usize, language: &LanguageDef, line_text: F) -> LineState
+    where
+        F: Fn(usize) -> String,
+    {
+        if self.language.as_deref() != Some(language.name.as_str()) {
+            self.language = Some(language.name.clone());
+            self.states.clear();
+        }
+        if self.states.is_empty() {
+            self.states.push(LineState::Normal);
+        }
+
+        while self.states.len() <= line {
+            let idx = self.states.len() - 1;
+            let (_, next) = tokenize_line(&line_text(idx), language, self.states[idx]);
+            self.states.push(next);
+        }
+
+        self.states[line]
+    }
+}
+
+fn mark(tokens: &mut [Option<TokenKind>], start: usize, end: usize, kind: TokenKind) {
+    let end = end.min(tokens.len());
+    for token in &mut tokens[start.min(end)..end] {
+        *token = Some(kind);
+    }
+}
+
+/// Indica si el texto `pattern` aparece en la posición `i`
+fn starts_with_at(chars: &[char], i: usize, pattern: &str) -> bool {
+    pattern
+        .chars()
+        .enumerate()
+        .all(|(k, c)| chars.get(i + k) == Some(&c))
 }
 
-fn starts_comment(chars: &[char], i: usize, language: SyntaxLanguage) -> bool {
-    match language {
-        SyntaxLanguage::Rust | SyntaxLanguage::JavaScript => {
-            i + 1 < chars.len() && chars[i] == '/' && chars[i + 1] == '/'
+/// Avanza dentro de un comentario de bloque hasta cerrarlo o llegar al final
+///
+/// # Retorna
+/// La posición después del comentario y el estado siguiente
+fn scan_block_comment(
+    chars: &[char],
+    mut i: usize,
+    mut depth: u32,
+    language: &LanguageDef,
+) -> (usize, LineState) {
+    let Some((open, close)) = &language.block_comment else {
+        return (i, LineState::Normal);
+    };
+
+    while i < chars.len() {
+        if starts_with_at(chars, i, close) {
+            i += close.chars().count();
+            depth -= 1;
+            if depth == 0 {
+                return (i, LineState::Normal);
+            }
+        } else if language.nested_comments && starts_with_at(chars, i, open) {
+            i += open.chars().count();
+            depth += 1;
+        } else {
+            i += 1;
         }
-        SyntaxLanguage::Python => chars[i] == '#',
-        SyntaxLanguage::PlainText => false,
     }
+
+    (i, LineState::BlockComment { depth })
 }
 
-fn is_ident_start(ch: char) -> bool {
-    ch.is_ascii_alphabetic() || ch == '_'
+/// Detecta la apertura de un string en `i`
+///
+/// # Retorna
+/// El delimitador que lo cierra y la cantidad de caracteres de la apertura
+fn string_start(
+    chars: &[char],
+    i: usize,
+    language: &LanguageDef,
+) -> Option<(StringDelimiter, usize)> {
+    if let Some(prefix) = &language.raw_string_prefix
+        && (i == 0 || !is_ident_continue(chars[i - 1]))
+        && starts_with_at(chars, i, prefix)
+    {
+        let after = i + prefix.chars().count();
+        let hashes = chars[after..].iter().take_while(|&&c| c == '#').count();
+        if chars.get(after + hashes) == Some(&'"') {
+            return Some((StringDelimiter::Raw(hashes), after - i + hashes + 1));
+        }
+    }
+
+    language
+        .strings
+        .iter()
+        .position(|rule| starts_with_at(chars, i, &rule.delimiter))
+        .map(|idx| {
+            let len = language.strings[idx].delimiter.chars().count();
+            (StringDelimiter::Rule(idx), len)
+        })
 }
 
-fn is_ident_continue(ch: char) -> bool {
-    ch.is_ascii_alphanumeric() || ch == '_'
+/// Detecta un literal de carácter o un lifetime en `i`
+///
+/// # Retorna
+/// El tipo de token y la posición donde termina
+fn quote_token(chars: &[char], i: usize, language: &LanguageDef) -> Option<(TokenKind, usize)> {
+    if !language.char_literals || chars[i] != '\'' {
+        return None;
+    }
+
+    match chars.get(i + 1)? {
+        // '\n', '\'', '\u{1F600}'
+        '\\' => {
+            let close = chars.get(i + 3..)?.iter().position(|&c| c == '\'')?;
+            Some((TokenKind::Char, i + 3 + close + 1))
+        }
+        _ if chars.get(i + 2) == Some(&'\'') => Some((TokenKind::Char, i + 3)),
+        &c if is_ident_start(c) => {
+            let end = i
+                + 1
+                + chars[i + 1..]
+                    .iter()
+                    .take_while(|&&c| is_ident_continue(c))
+                    .count();
+            Some((TokenKind::Lifetime, end))
+        }
+        _ => None,
+    }
 }
 
-fn is_keyword(word: &str, language: SyntaxLanguage) -> bool {
-    match language {
-        SyntaxLanguage::Rust => matches!(
-            word,
-            "fn" | "let"
-                | "mut"
-                | "if"
-                | "else"
-                | "match"
-                | "for"
-                | "while"
-                | "loop"
-                | "struct"
-                | "enum"
-                | "impl"
-                | "trait"
-                | "pub"
-                | "use"
-                | "mod"
-                | "const"
-                | "static"
-                | "return"
-                | "crate"
-                | "Self"
-                | "self"
-        ),
-        SyntaxLanguage::Python => matches!(
-            word,
-            "def"
-                | "class"
-                | "if"
-                | "elif"
-                | "else"
-                | "for"
-                | "while"
-                | "in"
-                | "import"
-                | "from"
-                | "return"
-                | "with"
-                | "as"
-                | "try"
-                | "except"
-                | "finally"
-                | "lambda"
-                | "True"
-                | "False"
-                | "None"
-        ),
-        SyntaxLanguage::JavaScript => matches!(
-            word,
-            "function"
-                | "const"
-                | "let"
-                | "var"
-                | "if"
-                | "else"
-                | "for"
-                | "while"
-                | "return"
-                | "class"
-                | "import"
-                | "from"
-                | "export"
-                | "new"
-                | "this"
-                | "true"
-                | "false"
-                | "null"
-                | "undefined"
+/// Detecta un atributo (`#[...]`, `#![...]`) o un decorador (`@property`)
+///
+/// # Retorna
+/// La posición donde termina, o `None` si no empieza uno en `i`
+fn attribute_end(chars: &[char], i: usize, language: &LanguageDef) -> Option<usize> {
+    if let Some(open) = language
+        .attributes
+        .iter()
+        .find(|open| starts_with_at(chars, i, open))
+    {
+        // La apertura termina en `[` (o `[[`): se busca el `]` que le
+        // corresponde, saltando los strings (`#[doc = "]"]`)
+        let mut depth = open.matches('[').count();
+        let mut idx = i + open.chars().count();
+        while idx < chars.len() {
+            if let Some((delimiter, open_len)) = string_start(chars, idx, language) {
+                let (end, _) = scan_string(chars, idx + open_len, delimiter, language);
+                idx = end;
+                continue;
+            }
+
+            match chars[idx] {
+                '[' => depth += 1,
+                ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(idx + 1);
+                    }
+                }
+                _ => {}
+            }
+            idx += 1;
+        }
+        return Some(chars.len());
+    }
+
+    if language.decorator == Some(chars[i]) && chars.get(i + 1).copied().is_some_and(is_ident_start)
+    {
+        let len = chars[i + 1..]
+            .iter()
+            .take_while(|&&c| is_ident_continue(c) || c == '.')
+            .count();
+        return Some(i + 1 + len);
+    }
+
+    None
+}
+
+/// Detecta una directiva al inicio de la línea (`#include`, `#define`)
+///
+/// # Retorna
+/// La posición después del nombre de la directiva
+fn directive_end(chars: &[char], i: usize, language: &LanguageDef) -> Option<usize> {
+    let marker = language
+        .directives
+        .iter()
+        .find(|marker| starts_with_at(chars, i, marker))?;
+    let start = i + marker.chars().count();
+    let len = chars[start..]
+        .iter()
+        .take_while(|&&c| is_ident_continue(c))
+        .count();
+
+    (len > 0).then_some(start + len)
+}
+
+/// Detecta una variable (`$HOME`, `${nombre}`, `$1`, `$@`)
+///
+/// # Retorna
+/// La posición donde termina, o `None` si no empieza una en `i`
+fn variable_end(chars: &[char], i: usize, language: &LanguageDef) -> Option<usize> {
+    if language.variable_prefix != Some(chars[i]) {
+        return None;
+    }
+
+    match *chars.get(i + 1)? {
+        '{' => {
+            let close = chars[i + 2..].iter().position(|&c| c == '}');
+            Some(close.map_or(chars.len(), |close| i + 2 + close + 1))
+        }
+        c if is_ident_start(c) => Some(
+            i + 1
+                + chars[i + 1..]
+                    .iter()
+                    .take_while(|&&c| is_ident_continue(c))
+                    .count(),
         ),
-        SyntaxLanguage::PlainText => false,
+        c if c.is_ascii_digit() || "@*#?$!-".contains(c) => Some(i + 2),
+        _ => None,
+    }
+}
+
+/// Indica si en los strings con este delimitador se resaltan las variables
+fn interpolates(delimiter: StringDelimiter, language: &LanguageDef) -> bool {
+    match delimiter {
+        StringDelimiter::Rule(idx) => language
+            .strings
+            .get(idx)
+            .is_some_and(|rule| language.interpolated_strings.contains(&rule.delimiter)),
+        StringDelimiter::Raw(_) => false,
     }
 }
 
+/// Indica si en el string de la regla `idx` se procesan los escapes
+fn escapes(idx: usize, language: &LanguageDef) -> bool {
+    language
+        .strings
+        .get(idx)
+        .is_some_and(|rule| !language.unescaped_strings.contains(&rule.delimiter))
+}
+
+/// Marca las variables dentro del string entre `start` y `end`
+fn mark_variables(
+    tokens: &mut [Option<TokenKind>],
+    chars: &[char],
+    start: usize,
+    end: usize,
+    language: &LanguageDef,
+) {
+    let mut i = start;
+    while i < end {
+        if Some(chars[i]) == language.escape {
+            i += 2;
+        } else if let Some(var_end) = variable_end(chars, i, language) {
+            let var_end = var_end.min(end);
+            mark(tokens, i, var_end, TokenKind::Variable);
+            i = var_end;
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Detecta una clave seguida del separador del lenguaje (`clave = `, `"clave":`)
+///
+/// La clave tiene que ser lo primero de la línea o venir después de `{`,
+/// `,`, `;` o `-` (elementos de listas de YAML), así no se confunde un valor
+/// como `http://...` con una clave.
+///
+/// # Retorna
+/// La posición donde termina la clave, sin el separador
+fn key_end(chars: &[char], i: usize, language: &LanguageDef) -> Option<usize> {
+    let separator = language.key_separator.as_ref()?;
+    let prev = chars[..i].iter().rev().find(|c| !c.is_whitespace());
+    if !matches!(prev, None | Some('{' | ',' | ';' | '-')) {
+        return None;
+    }
+
+    let (end, quoted) = match string_start(chars, i, language) {
+        Some((delimiter, open_len)) => {
+            let (end, closed) = scan_string(chars, i + open_len, delimiter, language);
+            if !closed {
+                return None;
+            }
+            (end, true)
+        }
+        None if is_ident_continue(chars[i]) => {
+            let len = chars[i..]
+                .iter()
+                .take_while(|&&c| is_ident_continue(c) || c == '-' || c == '.')
+                .count();
+            (i + len, false)
+        }
+        None => return None,
+    };
+
+    let sep = end
+        + chars[end..]
+            .iter()
+            .take_while(|c| c.is_whitespace())
+            .count();
+    if !starts_with_at(chars, sep, separator) {
+        return None;
+    }
+
+    // En YAML `clave:` va seguido de un espacio; `a:b` no es una clave
+    let after = chars.get(sep + separator.chars().count());
+    if separator == ":" && !quoted && after.is_some_and(|c| !c.is_whitespace()) {
+        return None;
+    }
+
+    Some(end)
+}
+
+/// Detecta una etiqueta de HTML: `<nombre`, `</nombre`, `<!DOCTYPE`, `>` o `/>`
+///
+/// `>` y `/>` solo cuentan si cierran una etiqueta abierta (`in_tag`).
+///
+/// # Retorna
+/// La posición donde termina, o `None` si no empieza una en `i`
+fn tag_end(chars: &[char], i: usize, in_tag: bool, language: &LanguageDef) -> Option<usize> {
+    if !language.tags {
+        return None;
+    }
+
+    match chars[i] {
+        '<' => {
+            let start = i + 1 + usize::from(matches!(chars.get(i + 1), Some('/' | '!')));
+            if !chars.get(start).copied().is_some_and(is_ident_start) {
+                return None;
+            }
+            let len = chars[start..]
+                .iter()
+                .take_while(|&&c| is_ident_continue(c) || c == '-' || c == ':')
+                .count();
+            Some(start + len)
+        }
+        '>' if in_tag => Some(i + 1),
+        '/' if in_tag && chars.get(i + 1) == Some(&'>') => Some(i + 2),
+        _ => None,
+    }
+}
+
+/// Detecta texto con énfasis de Markdown (`*cursiva*`, `**negrita**`, `_x_`)
+///
+/// El delimitador de apertura no puede ir pegado a una palabra anterior ni
+/// seguido de un espacio, así `snake_case` y las listas `* item` no cuentan.
+///
+/// # Retorna
+/// La posición después del delimitador de cierre
+fn emphasis_end(chars: &[char], i: usize, language: &LanguageDef) -> Option<usize> {
+    if i > 0 && chars[i - 1].is_alphanumeric() {
+        return None;
+    }
+
+    language.emphasis.iter().find_map(|delimiter| {
+        let len = delimiter.chars().count();
+        if !starts_with_at(chars, i, delimiter)
+            || chars.get(i + len).is_none_or(|c| c.is_whitespace())
+        {
+            return None;
+        }
+
+        (i + len + 1..chars.len())
+            .find(|&j| {
+                starts_with_at(chars, j, delimiter)
+                    && !chars[j - 1].is_whitespace()
+                    && chars.get(j + len).is_none_or(|c| !c.is_alphanumeric())
+            })
+            .map(|j| j + len)
+    })
+}
+
+/// Avanza dentro de un string hasta cerrarlo o llegar al final de la línea
+///
+/// # Retorna
+/// La posición después del string y si se cerró en esta línea
+fn scan_string(
+    chars: &[char],
+    mut i: usize,
+    delimiter: StringDelimiter,
+    language: &LanguageDef,
+) -> (usize, bool) {
+    let close = match delimiter {
+        StringDelimiter::Rule(idx) => language.strings.get(idx).map(|rule| &rule.delimiter),
+        StringDelimiter::Raw(_) => None,
+    };
+
+    while i < chars.len() {
+        match (delimiter, close) {
+            (StringDelimiter::Raw(hashes), _)
+                if chars[i] == '"'
+                    && chars[i + 1..].iter().take_while(|&&c| c == '#').count() >= hashes =>
+            {
+                return (i + 1 + hashes, true);
+            }
+            (StringDelimiter::Rule(idx), _)
+                if Some(chars[i]) == language.escape && escapes(idx, language) =>
+            {
+                i += 2;
+                continue;
+            }
+            (StringDelimiter::Rule(_), Some(close)) if starts_with_at(chars, i, close) => {
+                return (i + close.chars().count(), true);
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    (chars.len(), false)
+}
+
+/// Posición donde termina el número que empieza en `start`
+///
+/// Admite los prefijos de base del lenguaje (`0x`), el separador de dígitos,
+/// decimales, exponente y, si el lenguaje los usa, sufijos de tipo (`1u8`).
+fn number_end(chars: &[char], start: usize, language: &LanguageDef) -> usize {
+    let is_digit = |c: char, hex: bool| {
+        c.is_ascii_digit() || (hex && c.is_ascii_hexdigit()) || Some(c) == language.number_separator
+    };
+    let digits_end = |mut i: usize, hex: bool| {
+        while i < chars.len() && is_digit(chars[i], hex) {
+            i += 1;
+        }
+        i
+    };
+
+    let prefix = language.number_prefixes.iter().find(|prefix| {
+        prefix.chars().enumerate().all(|(k, c)| {
+            chars
+                .get(start + k)
+                .is_some_and(|d| d.eq_ignore_ascii_case(&c))
+        })
+    });
+
+    let mut i = match prefix {
+        Some(prefix) => digits_end(start + prefix.chars().count(), true),
+        None => {
+            let mut i = digits_end(start, false);
+            if chars.get(i) == Some(&'.') && chars.get(i + 1).is_some_and(char::is_ascii_digit) {
+                i = digits_end(i + 1, false);
+            }
+            if matches!(chars.get(i), Some('e' | 'E')) {
+                let sign = usize::from(matches!(chars.get(i + 1), Some('+' | '-')));
+                if chars.get(i + 1 + sign).is_some_and(char::is_ascii_digit) {
+                    i = digits_end(i + 1 + sign, false);
+                }
+            }
+            i
+        }
+    };
+
+    if language.number_suffixes {
+        while i < chars.len() && is_ident_continue(chars[i]) {
+            i += 1;
+        }
+    }
+
+    i
+}
+
+/// Identificadores en CamelCase (no las constantes en mayúsculas) y los
+/// tipos declarados por el lenguaje
+fn is_type_name(word: &str, language: &LanguageDef) -> bool {
+    let camel_case = word.starts_with(|c: char| c.is_ascii_uppercase())
+        && word.chars().any(|c| c.is_ascii_lowercase());
+
+    camel_case || language.is_type(word)
+}
+
+fn is_operator(ch: char) -> bool {
+    matches!(
+        ch,
+        '+' | '-'
+            | '*'
+            | '/'
+            | '%'
+            | '='
+            | '&'
+            | '|'
+            | '^'
+            | '!'
+            | '<'
+            | '>'
+            | '?'
+            | ':'
+            | ';'
+            | ','
+            | '.'
+            | '('
+            | ')'
+            | '{'
+            | '}'
+            | '['
+            | ']'
+            | '~'
+            | '@'
+    )
+}
+
+fn is_ident_start(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || ch == '_'
+}
+
+fn is_ident_continue(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::language::Languages;
 
-    #[test]
-    fn detect_language_by_extension() {
-        assert_eq!(detect_language(Some("main.rs")), SyntaxLanguage::Rust);
-        assert_eq!(detect_language(Some("app.py")), SyntaxLanguage::Python);
-        assert_eq!(detect_language(Some("app.ts")), SyntaxLanguage::JavaScript);
-        assert_eq!(
-            detect_language(Some("notes.txt")),
-            SyntaxLanguage::PlainText
-        );
+    /// Definición incluida para los archivos con esa extensión
+    fn lang(ext: &str) -> LanguageDef {
+        Languages::builtin()
+            .detect(Some(&format!("archivo.{}", ext)))
+            .unwrap()
+            .clone()
     }
 
     #[test]
     fn rust_line_tokenization() {
         let line = "let value = 42 // comentario";
-        let tokens = tokenize_line(line, SyntaxLanguage::Rust);
+        let (tokens, _) = tokenize_line(line, &lang("rs"), LineState::Normal);
 
         assert_eq!(tokens[0], Some(TokenKind::Keyword));
         assert_eq!(tokens[12], Some(TokenKind::Number));
         assert_eq!(tokens[15], Some(TokenKind::Comment));
     }
+
+    /// Tokeniza varias líneas arrastrando el estado, como `SyntaxCache`
+    fn tokenize_lines(lines: &[&str], language: &LanguageDef) -> Vec<Vec<Option<TokenKind>>> {
+        let mut state = LineState::Normal;
+        lines
+            .iter()
+            .map(|line| {
+                let (tokens, next) = tokenize_line(line, language, state);
+                state = next;
+                tokens
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rust_block_comments_span_lines_and_nest() {
+        let lines = ["let a = 1; /* inicio", "/* anidado */ fn", "fin */ let b"];
+        let tokens = tokenize_lines(&lines, &lang("rs"));
+
+        assert_eq!(tokens[0][0], Some(TokenKind::Keyword));
+        assert_eq!(tokens[0][11], Some(TokenKind::Comment));
+        assert!(tokens[1].iter().all(|t| *t == Some(TokenKind::Comment)));
+        assert_eq!(tokens[2][5], Some(TokenKind::Comment));
+        assert_eq!(tokens[2][7], Some(TokenKind::Keyword));
+    }
+
+    #[test]
+    fn rust_strings_and_raw_strings_span_lines() {
+        let lines = ["let s = \"uno", "fn dos\";", "let r = r#\"a\"", "b\"# + 1"];
+        let tokens = tokenize_lines(&lines, &lang("rs"));
+
+        assert_eq!(tokens[0][8], Some(TokenKind::String));
+        assert!(tokens[1][..7].iter().all(|t| *t == Some(TokenKind::String)));
+        assert_eq!(tokens[1][7], Some(TokenKind::Operator));
+        assert_eq!(tokens[2][8], Some(TokenKind::String));
+        assert!(tokens[3][..3].iter().all(|t| *t == Some(TokenKind::String)));
+        assert_eq!(tokens[3][6], Some(TokenKind::Number));
+    }
+
+    #[test]
+    fn rust_char_literals_do_not_span_lines() {
+        let (_, state) = tokenize_line("let c = '", &lang("rs"), LineState::Normal);
+
+        assert_eq!(state, LineState::Normal);
+    }
+
+    #[test]
+    fn javascript_template_literals_and_block_comments_span_lines() {
+        let lines = ["const t = `hola", "${x} const", "`; /* a", "b */ let"];
+        let tokens = tokenize_lines(&lines, &lang("js"));
+
+        assert_eq!(tokens[0][10], Some(TokenKind::String));
+        assert_eq!(tokens[1][6], Some(TokenKind::String));
+        assert_eq!(tokens[2][0], Some(TokenKind::String));
+        assert_eq!(tokens[2][3], Some(TokenKind::Comment));
+        assert_eq!(tokens[3][0], Some(TokenKind::Comment));
+        assert_eq!(tokens[3][5], Some(TokenKind::Keyword));
+    }
+
+    #[test]
+    fn javascript_quotes_do_not_span_lines() {
+        let (_, state) = tokenize_line("let s = \"abc", &lang("js"), LineState::Normal);
+
+        assert_eq!(state, LineState::Normal);
+    }
+
+    #[test]
+    fn python_docstrings_span_lines() {
+        let lines = [
+            "def f():",
+            "    \"\"\"Docstring",
+            "    def no\"",
+            "    \"\"\" + 1",
+            "x = '''a'''",
+        ];
+        let tokens = tokenize_lines(&lines, &lang("py"));
+
+        assert_eq!(tokens[0][0], Some(TokenKind::Keyword));
+        assert_eq!(tokens[1][4], Some(TokenKind::String));
+        assert!(tokens[2].iter().all(|t| *t == Some(TokenKind::String)));
+        assert_eq!(tokens[3][6], Some(TokenKind::String));
+        assert_eq!(tokens[3][10], Some(TokenKind::Number));
+        assert_eq!(tokens[4][4], Some(TokenKind::String));
+        assert_eq!(tokens[4][10], Some(TokenKind::String));
+    }
+
+    #[test]
+    fn syntax_cache_recomputes_after_invalidation() {
+        let mut lines = [
+            "/* a".to_string(),
+            "b".to_string(),
+            "c */".to_string(),
+            "d".to_string(),
+        ];
+        let mut cache = SyntaxCache::default();
+
+        let state = cache.state_at(3, &lang("rs"), |idx| lines[idx].clone());
+        assert_eq!(state, LineState::Normal);
+        assert_eq!(
+            cache.state_at(1, &lang("rs"), |idx| lines[idx].clone()),
+            LineState::BlockComment { depth: 1 }
+        );
+
+        lines[0] = "a".to_string();
+        cache.invalidate_from(0);
+        assert_eq!(
+            cache.state_at(1, &lang("rs"), |idx| lines[idx].clone()),
+            LineState::Normal
+        );
+        assert_eq!(
+            cache.state_at(2, &lang("py"), |idx| lines[idx].clone()),
+            LineState::Normal
+        );
+    }
+
+    #[test]
+    fn rust_types_functions_and_macros() {
+        let line = "fn main() { let v: Vec<u8> = vec![1]; println!(\"{}\", foo(v)); }";
+        let (tokens, _) = tokenize_line(line, &lang("rs"), LineState::Normal);
+        let kind_at = |word: &str| tokens[line.find(word).unwrap()];
+
+        assert_eq!(kind_at("fn"), Some(TokenKind::Keyword));
+        assert_eq!(kind_at("main"), Some(TokenKind::Function));
+        assert_eq!(kind_at("Vec"), Some(TokenKind::Type));
+        assert_eq!(kind_at("u8"), Some(TokenKind::Type));
+        assert_eq!(kind_at("vec!"), Some(TokenKind::Macro));
+        assert_eq!(
+            tokens[line.find("vec!").unwrap() + 3],
+            Some(TokenKind::Macro)
+        );
+        assert_eq!(kind_at("println"), Some(TokenKind::Macro));
+        assert_eq!(kind_at("foo"), Some(TokenKind::Function));
+        assert_eq!(kind_at("{ let"), Some(TokenKind::Operator));
+        assert_eq!(kind_at(" = "), None);
+        assert_eq!(kind_at("= "), Some(TokenKind::Operator));
+    }
+
+    #[test]
+    fn rust_constants_and_inequality_are_not_types_or_macros() {
+        let line = "if MAX_LEN != x {}";
+        let (tokens, _) = tokenize_line(line, &lang("rs"), LineState::Normal);
+
+        assert_eq!(tokens[3], None);
+        assert_eq!(tokens[11], Some(TokenKind::Operator));
+        assert_eq!(tokens[12], Some(TokenKind::Operator));
+    }
+
+    #[test]
+    fn rust_attributes() {
+        let line = "#[derive(Debug, Clone)] struct A;";
+        let (tokens, _) = tokenize_line(line, &lang("rs"), LineState::Normal);
+
+        assert!(
+            tokens[..23]
+                .iter()
+                .all(|t| *t == Some(TokenKind::Attribute))
+        );
+        assert_eq!(tokens[24], Some(TokenKind::Keyword));
+
+        let (tokens, _) = tokenize_line("#![allow(dead_code)]", &lang("rs"), LineState::Normal);
+        assert!(tokens.iter().all(|t| *t == Some(TokenKind::Attribute)));
+    }
+
+    #[test]
+    fn rust_attribute_ends_after_strings_with_brackets() {
+        let line = "#[doc = \"]\"] fn a() {}";
+        let (tokens, _) = tokenize_line(line, &lang("rs"), LineState::Normal);
+
+        assert!(
+            tokens[..12]
+                .iter()
+                .all(|t| *t == Some(TokenKind::Attribute))
+        );
+        assert_eq!(tokens[13], Some(TokenKind::Keyword));
+        assert_eq!(tokens[16], Some(TokenKind::Function));
+    }
+
+    #[test]
+    fn anonymous_functions_do_not_mark_parameters() {
+        let line = "const f = function (a, b) { return a; };";
+        let (tokens, _) = tokenize_line(line, &lang("js"), LineState::Normal);
+
+        assert_eq!(tokens[line.find("(a").unwrap() + 1], None);
+
+        let line = "function  named(a) {}";
+        let (tokens, _) = tokenize_line(line, &lang("js"), LineState::Normal);
+        assert_eq!(
+            tokens[line.find("named").unwrap()],
+            Some(TokenKind::Function)
+        );
+    }
+
+    #[test]
+    fn rust_lifetimes_and_char_literals() {
+        let line = "fn f<'a>(s: &'a str) -> char { 'x' } '\\n' '\\''";
+        let (tokens, state) = tokenize_line(line, &lang("rs"), LineState::Normal);
+        let start = |word: &str| line.find(word).unwrap();
+
+        assert_eq!(tokens[start("'a>")], Some(TokenKind::Lifetime));
+        assert_eq!(tokens[start("'a>") + 1], Some(TokenKind::Lifetime));
+        assert_eq!(tokens[start("'a>") + 2], Some(TokenKind::Operator));
+        assert_eq!(tokens[start("'a str") + 1], Some(TokenKind::Lifetime));
+        assert_eq!(tokens[start("str")], Some(TokenKind::Type));
+        assert!(
+            tokens[start("'x'")..start("'x'") + 3]
+                .iter()
+                .all(|t| *t == Some(TokenKind::Char))
+        );
+        assert_eq!(tokens[start("'\\n'") + 3], Some(TokenKind::Char));
+        assert_eq!(tokens[line.len() - 1], Some(TokenKind::Char));
+        assert_eq!(state, LineState::Normal);
+    }
+
+    #[test]
+    fn numbers_with_suffixes_and_decimals() {
+        let line = "x = 1.5 + 0xFF + 2u8..3";
+        let (tokens, _) = tokenize_line(line, &lang("rs"), LineState::Normal);
+
+        assert!(tokens[4..7].iter().all(|t| *t == Some(TokenKind::Number)));
+        assert!(tokens[10..14].iter().all(|t| *t == Some(TokenKind::Number)));
+        assert_eq!(tokens[20], Some(TokenKind::Operator));
+        assert_eq!(tokens[22], Some(TokenKind::Number));
+    }
+
+    #[test]
+    fn python_decorators_and_definitions() {
+        let line = "@app.route def handler(): return MyClass()";
+        let (tokens, _) = tokenize_line(line, &lang("py"), LineState::Normal);
+        let kind_at = |word: &str| tokens[line.find(word).unwrap()];
+
+        assert_eq!(kind_at("@app"), Some(TokenKind::Attribute));
+        assert_eq!(kind_at(".route"), Some(TokenKind::Attribute));
+        assert_eq!(kind_at("handler"), Some(TokenKind::Function));
+        assert_eq!(kind_at("MyClass"), Some(TokenKind::Type));
+    }
+
+    #[test]
+    fn user_defined_language_rules() {
+        let lua = LanguageDef::parse(
+            "lua",
+            r#"
+                line_comments = ["--"]
+                block_comment = ["--[[", "]]"]
+                strings = ['"', "'"]
+                keywords = ["local", "function", "end"]
+                definition_keywords = ["function"]
+            "#,
+        )
+        .unwrap();
+
+        let lines = [
+            "local s = 'a' -- fin",
+            "--[[ uno",
+            "dos ]] function f() end",
+        ];
+        let tokens = tokenize_lines(&lines, &lua);
+
+        assert_eq!(tokens[0][0], Some(TokenKind::Keyword));
+        assert_eq!(tokens[0][10], Some(TokenKind::String));
+        assert_eq!(tokens[0][14], Some(TokenKind::Comment));
+        assert!(tokens[1].iter().all(|t| *t == Some(TokenKind::Comment)));
+        assert_eq!(tokens[2][5], Some(TokenKind::Comment));
+        assert_eq!(tokens[2][7], Some(TokenKind::Keyword));
+        assert_eq!(tokens[2][16], Some(TokenKind::Function));
+    }
+
+    #[test]
+    fn markdown_headings_emphasis_and_code_fences() {
+        let lines = [
+            "# Título",
+            "texto *cursiva* y **negrita** en snake_case, `código`",
+            "* item",
+            "```rust",
+            "# no es título",
+            "```",
+        ];
+        let tokens = tokenize_lines(&lines, &lang("md"));
+
+        assert!(tokens[0].iter().all(|t| *t == Some(TokenKind::Heading)));
+        let line = lines[1];
+        let col = |text: &str| line[..line.find(text).unwrap()].chars().count();
+        assert_eq!(tokens[1][0], None);
+        assert_eq!(tokens[1][col("*cursiva*")], Some(TokenKind::Emphasis));
+        assert_eq!(tokens[1][col("a* y")], Some(TokenKind::Emphasis));
+        assert_eq!(tokens[1][col("negrita")], Some(TokenKind::Emphasis));
+        assert_eq!(tokens[1][col("_case")], None);
+        assert_eq!(tokens[1][col("código")], Some(TokenKind::String));
+        assert_eq!(tokens[2][0], None);
+        assert!(tokens[4].iter().all(|t| *t == Some(TokenKind::String)));
+        assert_eq!(tokens[5][2], Some(TokenKind::String));
+    }
+
+    #[test]
+    fn toml_tables_and_keys() {
+        let lines = [
+            "[editor] # opciones",
+            "backup = true",
+            "dep = { version = \"1\", features = [\"x\"] }",
+        ];
+        let tokens = tokenize_lines(&lines, &lang("toml"));
+
+        assert!(
+            tokens[0][..9]
+                .iter()
+                .all(|t| *t == Some(TokenKind::Heading))
+        );
+        assert_eq!(tokens[0][9], Some(TokenKind::Comment));
+        assert_eq!(tokens[1][0], Some(TokenKind::Key));
+        assert_eq!(tokens[1][9], Some(TokenKind::Keyword));
+        assert_eq!(tokens[2][8], Some(TokenKind::Key));
+        assert_eq!(tokens[2][18], Some(TokenKind::String));
+        assert_eq!(tokens[2][23], Some(TokenKind::Key));
+        assert_eq!(tokens[2][35], Some(TokenKind::String));
+    }
+
+    #[test]
+    fn json_keys_differ_from_values() {
+        let line = "{\"nombre\": \"valor\", \"lista\":[\"a\", 1, true]}";
+        let (tokens, _) = tokenize_line(line, &lang("json"), LineState::Normal);
+        let kind_at = |text: &str| tokens[line.find(text).unwrap()];
+
+        assert_eq!(kind_at("\"nombre\""), Some(TokenKind::Key));
+        assert_eq!(kind_at("\"valor\""), Some(TokenKind::String));
+        assert_eq!(kind_at("\"lista\""), Some(TokenKind::Key));
+        assert_eq!(kind_at("\"a\""), Some(TokenKind::String));
+        assert_eq!(kind_at("1,"), Some(TokenKind::Number));
+        assert_eq!(kind_at("true"), Some(TokenKind::Keyword));
+    }
+
+    #[test]
+    fn yaml_keys_need_a_space_after_the_colon() {
+        let lines = ["url: http://ejemplo.com", "  - nombre: &base x # nota"];
+        let tokens = tokenize_lines(&lines, &lang("yaml"));
+
+        assert!(tokens[0][..3].iter().all(|t| *t == Some(TokenKind::Key)));
+        assert_eq!(tokens[0][5], None);
+        assert_eq!(tokens[1][4], Some(TokenKind::Key));
+        assert_eq!(tokens[1][12], Some(TokenKind::Attribute));
+        assert_eq!(tokens[1][21], Some(TokenKind::Comment));
+    }
+
+    #[test]
+    fn shell_variables_inside_and_outside_strings() {
+        let line = "echo $HOME \"${dir}/x $1\" '$no' $# # comentario";
+        let (tokens, _) = tokenize_line(line, &lang("sh"), LineState::Normal);
+        let kind_at = |text: &str| tokens[line.find(text).unwrap()];
+
+        assert_eq!(kind_at("$HOME"), Some(TokenKind::Variable));
+        assert_eq!(kind_at("${dir}"), Some(TokenKind::Variable));
+        assert_eq!(kind_at("}/x"), Some(TokenKind::Variable));
+        assert_eq!(kind_at("/x"), Some(TokenKind::String));
+        assert_eq!(kind_at("$1"), Some(TokenKind::Variable));
+        assert_eq!(kind_at("$no"), Some(TokenKind::String));
+        assert_eq!(kind_at("$#"), Some(TokenKind::Variable));
+        assert_eq!(kind_at("# comentario"), Some(TokenKind::Comment));
+    }
+
+    #[test]
+    fn shell_single_quotes_have_no_escapes() {
+        let line = "echo 'C:\\' $HOME";
+        let (tokens, state) = tokenize_line(line, &lang("sh"), LineState::Normal);
+
+        assert_eq!(
+            tokens[line.find("\\'").unwrap() + 1],
+            Some(TokenKind::String)
+        );
+        assert_eq!(
+            tokens[line.find("$HOME").unwrap()],
+            Some(TokenKind::Variable)
+        );
+        assert_eq!(state, LineState::Normal);
+
+        let line = "echo \"a\\\" b\"";
+        let (tokens, state) = tokenize_line(line, &lang("sh"), LineState::Normal);
+        assert_eq!(tokens[line.len() - 1], Some(TokenKind::String));
+        assert_eq!(state, LineState::Normal);
+    }
+
+    #[test]
+    fn c_directives_and_go_raw_strings() {
+        let (tokens, _) = tokenize_line("#include <stdio.h> // io", &lang("c"), LineState::Normal);
+        assert!(tokens[..8].iter().all(|t| *t == Some(TokenKind::Attribute)));
+        assert_eq!(tokens[9], Some(TokenKind::Operator));
+        assert_eq!(tokens[19], Some(TokenKind::Comment));
+
+        let lines = ["func main() { s := `uno", "dos` }"];
+        let tokens = tokenize_lines(&lines, &lang("go"));
+        assert_eq!(tokens[0][0], Some(TokenKind::Keyword));
+        assert_eq!(tokens[0][5], Some(TokenKind::Function));
+        assert_eq!(tokens[1][0], Some(TokenKind::String));
+        assert_eq!(tokens[1][5], Some(TokenKind::Operator));
+    }
+
+    #[test]
+    fn cpp_attributes_with_double_brackets() {
+        let line = "[[nodiscard]] int f();";
+        let (tokens, _) = tokenize_line(line, &lang("cpp"), LineState::Normal);
+
+        assert!(
+            tokens[..13]
+                .iter()
+                .all(|t| *t == Some(TokenKind::Attribute))
+        );
+        assert_eq!(tokens[14], Some(TokenKind::Type));
+    }
+
+    #[test]
+    fn typescript_has_its_own_keywords() {
+        let line = "interface A { x: number }";
+        let (ts, _) = tokenize_line(line, &lang("ts"), LineState::Normal);
+        let (js, _) = tokenize_line(line, &lang("js"), LineState::Normal);
+
+        assert_eq!(ts[0], Some(TokenKind::Keyword));
+        assert_eq!(ts[17], Some(TokenKind::Type));
+        assert_eq!(js[0], None);
+    }
+
+    #[test]
+    fn html_tags_and_sql_case_insensitive_keywords() {
+        let line = "<p class=\"a\">Hola 42</p> <!-- nota -->";
+        let (tokens, _) = tokenize_line(line, &lang("html"), LineState::Normal);
+        assert!(tokens[..2].iter().all(|t| *t == Some(TokenKind::Tag)));
+        assert_eq!(tokens[9], Some(TokenKind::String));
+        assert_eq!(tokens[12], Some(TokenKind::Tag));
+        assert_eq!(tokens[18], None);
+        assert!(tokens[21..24].iter().all(|t| *t == Some(TokenKind::Tag)));
+        assert_eq!(tokens[26], Some(TokenKind::Comment));
+
+        // Fuera de las etiquetas las comillas son texto
+        let line = "<a href='x'>It's \"ok\" > 1</a>";
+        let (tokens, _) = tokenize_line(line, &lang("html"), LineState::Normal);
+        assert_eq!(tokens[8], Some(TokenKind::String));
+        assert_eq!(tokens[11], Some(TokenKind::Tag));
+        assert_eq!(tokens[14], None);
+        assert_eq!(tokens[17], None);
+        assert_eq!(tokens[22], None);
+
+        // Atributos en varias líneas
+        let lines = ["<img", "  alt=\"it's\" />", "it's"];
+        let mut state = LineState::Normal;
+        let mut all = Vec::new();
+        for line in lines {
+            let (tokens, next) = tokenize_line(line, &lang("html"), state);
+            all.push(tokens);
+            state = next;
+        }
+        assert_eq!(all[1][6], Some(TokenKind::String));
+        assert_eq!(all[1][13], Some(TokenKind::Tag));
+        assert_eq!(all[2][2], None);
+        assert_eq!(state, LineState::Normal);
+
+        let line = "SELECT id FROM t where x = 'a' -- fin";
+        let (tokens, _) = tokenize_line(line, &lang("sql"), LineState::Normal);
+        assert_eq!(tokens[0], Some(TokenKind::Keyword));
+        assert_eq!(tokens[10], Some(TokenKind::Keyword));
+        assert_eq!(tokens[17], Some(TokenKind::Keyword));
+        assert_eq!(tokens[27], Some(TokenKind::String));
+        assert_eq!(tokens[31], Some(TokenKind::Comment));
+    }
+
+    #[test]
+    fn css_properties_and_units() {
+        let line = "a:hover { color: red; margin: 10px }";
+        let (tokens, _) = tokenize_line(line, &lang("css"), LineState::Normal);
+
+        assert_eq!(tokens[0], None);
+        assert_eq!(tokens[10], Some(TokenKind::Key));
+        assert_eq!(tokens[22], Some(TokenKind::Key));
+        assert!(tokens[30..34].iter().all(|t| *t == Some(TokenKind::Number)));
+    }
 }
@@ -1,5 +1,8 @@
 /// Terminal.rs
-use std::io::{self, Write};
+use std::{
+    io::{self, Write},
+    time::{Duration, Instant},
+};
 
 use crossterm::{
     ExecutableCommand, QueueableCommand, cursor,
@@ -7,6 +10,13 @@ use crossterm::{
     terminal::{self, ClearType},
 };
 
+use crate::{
+    completion::PathCompletion,
+    finder,
+    history::PromptHistory,
+    prompt::{self, HistoryBrowser, LineEditor, PromptAction},
+};
+
 // Constantes para declarar teclas de control
 pub mod keys {
     use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
@@ -46,6 +56,46 @@ pub mod keys {
     pub fn is_paste(key: &KeyEvent) -> bool {
         matches!(key.code, KeyCode::Char('v')) && key.modifiers.contains(KeyModifiers::CONTROL)
     }
+
+    pub fn is_cut(key: &KeyEvent) -> bool {
+        matches!(key.code, KeyCode::Char('x')) && key.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    pub fn is_replace(key: &KeyEvent) -> bool {
+        matches!(key.code, KeyCode::Char('r')) && key.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    pub fn is_command(key: &KeyEvent) -> bool {
+        matches!(key.code, KeyCode::Char('e')) && key.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    pub fn is_undo(key: &KeyEvent) -> bool {
+        matches!(key.code, KeyCode::Char('z')) && key.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    pub fn is_redo(key: &KeyEvent) -> bool {
+        matches!(key.code, KeyCode::Char('y')) && key.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    pub fn is_buffer_list(key: &KeyEvent) -> bool {
+        matches!(key.code, KeyCode::Char('b')) && key.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    pub fn is_close_buffer(key: &KeyEvent) -> bool {
+        matches!(key.code, KeyCode::Char('w')) && key.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    pub fn is_find_file(key: &KeyEvent) -> bool {
+        matches!(key.code, KeyCode::Char('t')) && key.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    pub fn is_next_buffer(key: &KeyEvent) -> bool {
+        matches!(key.code, KeyCode::PageDown) && key.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    pub fn is_prev_buffer(key: &KeyEvent) -> bool {
+        matches!(key.code, KeyCode::PageUp) && key.modifiers.contains(KeyModifiers::CONTROL)
+    }
 }
 
 // Constantes para manejar el estado por defecto
@@ -59,14 +109,51 @@ pub mod messages {
     pub const INVALID_FORMAT: &str = "Formato inválido. Use: linea,columna";
     pub const INVALID_NUMBERS: &str = "Ingrese números válidos";
     pub const LINES_START_AT_ONE: &str = "Las líneas y columnas empiezan en 1";
+    pub const NOTHING_TO_UNDO: &str = "No hay cambios para deshacer";
+    pub const NOTHING_TO_REDO: &str = "No hay cambios para rehacer";
+    pub const UNDONE: &str = "Cambio deshecho";
+    pub const REDONE: &str = "Cambio rehecho";
+    pub const UNSAVED_CHANGES_PROMPT: &str =
+        "Hay cambios sin guardar. ¿Guardar? (s)í / (n)o / (c)ancelar: ";
+    pub const ACTION_CANCELLED: &str = "Acción cancelada";
+    pub const SEARCH_PROMPT: &str =
+        "Buscar [re: regex, i: ignorar may., s: smart-case, w: palabra]: ";
+    pub const REPLACE_SEARCH_PROMPT: &str =
+        "Reemplazar [re: regex, i: ignorar may., s: smart-case, w: palabra]: ";
+    pub const REPLACE_CANCELLED: &str = "Reemplazo cancelado";
+    pub const COMMAND_CANCELLED: &str = "Comando cancelado";
+    pub const GOTO_CANCELLED: &str = "Salto de línea cancelado";
+    pub const REPLACE_PROMPT: &str = "¿Reemplazar? (s)í / (n)o / (t)odas / (q) salir: ";
+    pub const RECOVERY_PROMPT: &str =
+        "Hay un archivo de recuperación. (r)ecuperar / (d)iferencias / (b)orrar / Esc conservar: ";
+    pub const RECOVERY_DIFF_TITLE: &str =
+        "Diferencias con la recuperación (- en disco, + recuperado) - Esc para volver";
+    pub const EXTERNAL_CHANGE_PROMPT: &str =
+        "El archivo cambió en disco. (r)ecargar / (s)obrescribir / (a)mbos / Esc cancelar: ";
+    pub const CLOSE_BUFFER_PROMPT: &str =
+        "El buffer tiene cambios sin guardar. ¿Guardar antes de cerrar? (s)í / (n)o / (c)ancelar: ";
+    pub const READ_ONLY: &str =
+        "Buffer de solo lectura (Ctrl+E y 'solo-lectura' para permitir cambios)";
+    pub const FIND_FILE_PROMPT: &str = "Archivo: ";
+    pub const GREP_PROMPT: &str =
+        "Buscar en archivos [re: regex, i: ignorar may., s: smart-case, w: palabra]: ";
+    pub const NO_FILES_FOUND: &str = "No se encontraron archivos en el directorio actual";
+    pub const BUFFER_LIST_TITLE: &str = "Buffers abiertos (Enter: cambiar, Esc: cancelar)";
 }
 
 pub fn init_raw_mode() -> io::Result<io::Stdout> {
     terminal::enable_raw_mode()?;
-    Ok(io::stdout())
+    let mut stdout = io::stdout();
+    // Necesario para recibir `Event::FocusGained` al volver a la terminal
+    stdout.execute(event::EnableFocusChange)?;
+    // El texto pegado llega como un único `Event::Paste` en vez de teclas sueltas
+    stdout.execute(event::EnableBracketedPaste)?;
+    Ok(stdout)
 }
 
 pub fn cleanup() -> io::Result<()> {
+    io::stdout().execute(event::DisableBracketedPaste)?;
+    io::stdout().execute(event::DisableFocusChange)?;
     terminal::disable_raw_mode()?;
     io::stdout().execute(cursor::Show)?;
     Ok(())
@@ -75,30 +162,259 @@ pub fn cleanup() -> io::Result<()> {
 /// Lee el siguiente evento del terminal
 pub fn read_event() -> io::Result<Event> {
     loop {
-        let event = event::read()?;
-        match event {
-            Event::Key(key) if key.kind == KeyEventKind::Press => return Ok(Event::Key(key)),
-            Event::Resize(_, _) => return Ok(event),
-            Event::Mouse(_) | Event::FocusGained | Event::FocusLost | Event::Paste(_) => {
-                return Ok(event);
-            }
-            _ => {}
+        if let Some(event) = filter_event(event::read()?) {
+            return Ok(event);
+        }
+    }
+}
+
+/// Espera un evento del terminal como máximo `timeout`
+///
+/// # Retorna
+/// El evento leído, o `None` si se agotó el tiempo sin eventos relevantes
+pub fn poll_event(timeout: Duration) -> io::Result<Option<Event>> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if !event::poll(remaining)? {
+            return Ok(None);
+        }
+        if let Some(event) = filter_event(event::read()?) {
+            return Ok(Some(event));
         }
     }
 }
 
+/// Descarta los eventos que el editor no procesa (como soltar una tecla)
+fn filter_event(event: Event) -> Option<Event> {
+    match event {
+        Event::Key(key) if key.kind == KeyEventKind::Press => Some(event),
+        Event::Key(_) => None,
+        _ => Some(event),
+    }
+}
+
 /// Solicita entrada del usuario con un prompt
 ///
+/// Las flechas arriba/abajo recorren `history`, al que se agrega el texto
+/// confirmado.
+///
 /// # Argumentos
 /// * `stdout` - Terminal en modo raw donde escribir
 /// * `prompt` - Mensaje a mostrar al usuario
+/// * `history` - Historial de este tipo de prompt
 ///
 /// # Retorna
-/// El texto ingresado por el usuario
-pub fn request_input<W: Write>(stdout: &mut W, prompt: &str) -> String {
-    let mut user_input = String::new();
+/// El texto ingresado, o `None` si el usuario canceló con Esc
+pub fn request_input<W: Write>(
+    stdout: &mut W,
+    prompt: &str,
+    history: &mut PromptHistory,
+) -> Option<String> {
+    request_input_with(stdout, prompt, "", history, None, |_, _| {})
+}
 
-    // Obtener la altura de la terminal
+/// Solicita una ruta de archivo, con autocompletado al pulsar Tab
+///
+/// Los candidatos se listan en la fila de estado cuando hay más de uno
+/// (ver `completion::PathCompletion`).
+///
+/// # Argumentos
+/// * `stdout` - Terminal en modo raw donde escribir
+/// * `prompt` - Mensaje a mostrar al usuario
+/// * `default` - Texto inicial, que el usuario puede editar
+/// * `history` - Historial de este tipo de prompt
+///
+/// # Retorna
+/// El texto ingresado, o `None` si el usuario canceló con Esc
+pub fn request_path<W: Write>(
+    stdout: &mut W,
+    prompt: &str,
+    default: &str,
+    history: &mut PromptHistory,
+) -> Option<String> {
+    let mut completion = PathCompletion::new();
+    request_input_with(
+        stdout,
+        prompt,
+        default,
+        history,
+        Some(&mut completion),
+        |_, _| {},
+    )
+}
+
+/// Solicita entrada del usuario notificando cada cambio del texto
+///
+/// Después de cada tecla que modifica el texto se invoca `on_change`, que
+/// puede redibujar la pantalla; luego se vuelve a dibujar el prompt.
+///
+/// # Argumentos
+/// * `stdout` - Terminal en modo raw donde escribir
+/// * `prompt` - Mensaje a mostrar al usuario
+/// * `history` - Historial de este tipo de prompt
+/// * `on_change` - Callback con el texto ingresado hasta el momento
+///
+/// # Retorna
+/// El texto ingresado, o `None` si el usuario canceló con Esc
+pub fn request_input_incremental<W, F>(
+    stdout: &mut W,
+    prompt: &str,
+    history: &mut PromptHistory,
+    on_change: F,
+) -> Option<String>
+where
+    W: Write,
+    F: FnMut(&mut W, &str),
+{
+    request_input_with(stdout, prompt, "", history, None, on_change)
+}
+
+/// Bucle común de los prompts de texto (ver `prompt::LineEditor`)
+fn request_input_with<W, F>(
+    stdout: &mut W,
+    prompt: &str,
+    default: &str,
+    history: &mut PromptHistory,
+    completion: Option<&mut PathCompletion>,
+    on_change: F,
+) -> Option<String>
+where
+    W: Write,
+    F: FnMut(&mut W, &str),
+{
+    let input = edit_line(
+        stdout,
+        prompt,
+        default,
+        history.entries(),
+        completion,
+        on_change,
+    );
+    if let Some(text) = &input {
+        history.push(text);
+    }
+    input
+}
+
+/// Edita la línea del prompt hasta que el usuario confirma o cancela
+fn edit_line<W, F>(
+    stdout: &mut W,
+    prompt: &str,
+    default: &str,
+    history: &[String],
+    mut completion: Option<&mut PathCompletion>,
+    mut on_change: F,
+) -> Option<String>
+where
+    W: Write,
+    F: FnMut(&mut W, &str),
+{
+    let mut editor = LineEditor::new(default);
+    let mut browser = HistoryBrowser::new(history);
+    render_prompt(stdout, prompt, &editor);
+
+    // La lista de candidatos de Tab está dibujada sobre la fila de mensajes
+    let mut listing = false;
+
+    while let Ok(event) = read_event() {
+        let is_tab = matches!(&event, Event::Key(key) if key.code == KeyCode::Tab);
+        let action = match event {
+            Event::Key(key) if key.code == KeyCode::Up => match browser.older(&editor.text()) {
+                Some(entry) => {
+                    editor.set_text(entry);
+                    PromptAction::Edited
+                }
+                None => PromptAction::Ignored,
+            },
+            Event::Key(key) if key.code == KeyCode::Down => match browser.newer() {
+                Some(entry) => {
+                    editor.set_text(&entry);
+                    PromptAction::Edited
+                }
+                None => PromptAction::Ignored,
+            },
+            Event::Key(key) if key.code == KeyCode::Tab => match completion.as_deref_mut() {
+                Some(completion) => complete_path(stdout, &mut editor, completion, &mut listing),
+                None => PromptAction::Ignored,
+            },
+            Event::Key(key) => editor.handle_key(&key),
+            Event::Paste(text) => {
+                editor.insert_str(&text);
+                PromptAction::Edited
+            }
+            Event::Resize(_, _) => PromptAction::Moved,
+            _ => PromptAction::Ignored,
+        };
+
+        if listing && !is_tab && action != PromptAction::Ignored {
+            clear_completion_list(stdout);
+            listing = false;
+        }
+
+        match action {
+            PromptAction::Submit => return Some(editor.text()),
+            PromptAction::Cancel => return None,
+            PromptAction::Edited => on_change(stdout, &editor.text()),
+            PromptAction::Moved => {}
+            PromptAction::Ignored => continue,
+        }
+
+        render_prompt(stdout, prompt, &editor);
+    }
+
+    None
+}
+
+/// Completa la ruta del prompt y muestra los candidatos si hay varios
+///
+/// `listing` indica si la lista de candidatos está en pantalla; se borra
+/// cuando queda un solo candidato.
+fn complete_path<W: Write>(
+    stdout: &mut W,
+    editor: &mut LineEditor,
+    completion: &mut PathCompletion,
+    listing: &mut bool,
+) -> PromptAction {
+    let Some(text) = completion.complete(&editor.text()) else {
+        return PromptAction::Ignored;
+    };
+    editor.set_text(&text);
+
+    if completion.candidates().len() > 1 {
+        let (_, height) = terminal::size().unwrap_or((80, 24));
+        crate::ui::render_message(stdout, height.saturating_sub(2), &completion.summary());
+        *listing = true;
+    } else if *listing {
+        clear_completion_list(stdout);
+        *listing = false;
+    }
+    PromptAction::Edited
+}
+
+/// Borra la lista de candidatos de la fila de mensajes
+fn clear_completion_list<W: Write>(stdout: &mut W) {
+    let (_, height) = terminal::size().unwrap_or((80, 24));
+    crate::ui::render_message(stdout, height.saturating_sub(2), "");
+}
+
+/// Dibuja el prompt y el texto ingresado en la última fila de la terminal
+fn render_prompt<W: Write>(stdout: &mut W, prompt: &str, editor: &LineEditor) {
+    let (width, height) = terminal::size().unwrap_or((80, 24));
+    prompt::render(stdout, height - 1, width as usize, prompt, editor);
+}
+
+/// Solicita al usuario que elija una opción pulsando una sola tecla
+///
+/// # Argumentos
+/// * `stdout` - Terminal en modo raw donde escribir
+/// * `prompt` - Mensaje a mostrar al usuario
+/// * `options` - Teclas aceptadas (en minúscula)
+///
+/// # Retorna
+/// La opción elegida, o `None` si el usuario presionó Esc
+pub fn request_choice<W: Write>(stdout: &mut W, prompt: &str, options: &[char]) -> Option<char> {
     let (_, height) = terminal::size().unwrap_or((80, 24));
 
     stdout
@@ -112,20 +428,11 @@ pub fn request_input<W: Write>(stdout: &mut W, prompt: &str) -> String {
     while let Ok(event) = read_event() {
         if let Event::Key(key) = event {
             match key.code {
-                KeyCode::Enter => break,
+                KeyCode::Esc => return None,
                 KeyCode::Char(c) => {
-                    user_input.push(c);
-                    write!(stdout, "{}", c).unwrap();
-                    stdout.flush().unwrap();
-                }
-
-                KeyCode::Backspace => {
-                    if !user_input.is_empty() {
-                        user_input.pop();
-                        stdout.queue(cursor::MoveLeft(1)).unwrap();
-                        write!(stdout, " ").unwrap();
-                        stdout.queue(cursor::MoveLeft(1)).unwrap();
-                        stdout.flush().unwrap();
+                    let c = c.to_ascii_lowercase();
+                    if options.contains(&c) {
+                        return Some(c);
                     }
                 }
                 _ => {}
@@ -133,7 +440,128 @@ pub fn request_input<W: Write>(stdout: &mut W, prompt: &str) -> String {
         }
     }
 
-    user_input
+    None
+}
+
+/// Muestra una lista a pantalla completa y deja elegir un elemento con las flechas
+///
+/// `RePág`/`AvPág` mueven la selección una pantalla entera.
+///
+/// # Argumentos
+/// * `stdout` - Terminal en modo raw donde escribir
+/// * `title` - Título de la lista
+/// * `items` - Elementos a mostrar
+/// * `initial` - Elemento seleccionado al empezar
+///
+/// # Retorna
+/// El índice elegido con Enter, o `None` si el usuario presionó Esc
+pub fn request_selection<W: Write>(
+    stdout: &mut W,
+    title: &str,
+    items: &[String],
+    initial: usize,
+) -> Option<usize> {
+    if items.is_empty() {
+        return None;
+    }
+
+    let mut selected = initial.min(items.len() - 1);
+    crate::ui::render_list(stdout, title, items, Some(selected));
+
+    while let Ok(event) = read_event() {
+        let (_, height) = terminal::size().unwrap_or((80, 24));
+        let page = (height as usize).saturating_sub(2).max(1);
+
+        match event {
+            Event::Key(key) => match key.code {
+                KeyCode::Enter => return Some(selected),
+                KeyCode::Esc => return None,
+                KeyCode::Up => selected = selected.saturating_sub(1),
+                KeyCode::Down => selected = (selected + 1).min(items.len() - 1),
+                KeyCode::PageUp => selected = selected.saturating_sub(page),
+                KeyCode::PageDown => selected = (selected + page).min(items.len() - 1),
+                KeyCode::Home => selected = 0,
+                KeyCode::End => selected = items.len() - 1,
+                _ => continue,
+            },
+            Event::Resize(_, _) => {}
+            _ => continue,
+        }
+
+        crate::ui::render_list(stdout, title, items, Some(selected));
+    }
+
+    None
+}
+
+/// Muestra el buscador difuso y deja elegir un elemento escribiendo parte de su nombre
+///
+/// Cada cambio de la consulta vuelve a ordenar los elementos con
+/// `finder::rank`; las flechas mueven la selección.
+///
+/// # Argumentos
+/// * `stdout` - Terminal en modo raw donde escribir
+/// * `title` - Título del buscador
+/// * `prompt` - Mensaje del prompt de la consulta
+/// * `items` - Elementos entre los que buscar
+/// * `preview` - Genera la vista previa del elemento con el índice dado
+///
+/// # Retorna
+/// El índice en `items` elegido con Enter, o `None` si el usuario presionó Esc
+pub fn request_fuzzy<W, P>(
+    stdout: &mut W,
+    title: &str,
+    prompt: &str,
+    items: &[String],
+    mut preview: P,
+) -> Option<usize>
+where
+    W: Write,
+    P: FnMut(usize) -> Vec<String>,
+{
+    let mut query = LineEditor::new("");
+    let mut matches = finder::rank("", items);
+    let mut selected = 0;
+    let mut preview_lines = matches.first().map(|&idx| preview(idx)).unwrap_or_default();
+
+    loop {
+        crate::ui::render_finder(stdout, title, items, &matches, selected, &preview_lines);
+        render_prompt(stdout, prompt, &query);
+
+        let previous = matches.get(selected).copied();
+        match read_event().ok()? {
+            Event::Key(key) => {
+                let page = terminal::size().map_or(10, |(_, h)| h.saturating_sub(2) as usize);
+                match key.code {
+                    KeyCode::Up => selected = selected.saturating_sub(1),
+                    KeyCode::Down => selected += 1,
+                    KeyCode::PageUp => selected = selected.saturating_sub(page),
+                    KeyCode::PageDown => selected += page,
+                    _ => match query.handle_key(&key) {
+                        PromptAction::Submit => return matches.get(selected).copied(),
+                        PromptAction::Cancel => return None,
+                        PromptAction::Edited => {
+                            matches = finder::rank(&query.text(), items);
+                            selected = 0;
+                        }
+                        PromptAction::Moved | PromptAction::Ignored => {}
+                    },
+                }
+            }
+            Event::Paste(text) => {
+                query.insert_str(&text);
+                matches = finder::rank(&query.text(), items);
+                selected = 0;
+            }
+            _ => {}
+        }
+
+        selected = selected.min(matches.len().saturating_sub(1));
+        let current = matches.get(selected).copied();
+        if current != previous {
+            preview_lines = current.map(&mut preview).unwrap_or_default();
+        }
+    }
 }
 
 /// Limpiar pantalla y resetear cursor
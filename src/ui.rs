@@ -1,6 +1,7 @@
 use crate::config::SyntaxTheme;
+use crate::language::LanguageDef;
 use crate::search::SearchState;
-use crate::syntax::{SyntaxLanguage, detect_language, tokenize_line};
+use crate::syntax::{LineState, tokenize_line};
 use crossterm::{
     cursor,
     style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor},
@@ -8,11 +9,25 @@ use crossterm::{
 };
 use std::fmt::Write as _;
 use std::io::Write;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Resaltados de fondo que dependen del estado del editor
+#[derive(Clone, Copy)]
+pub struct LineHighlight {
+    pub is_current_line: bool,
+
+    /// Rango de columnas seleccionadas en la línea (fin exclusivo)
+    pub selection: Option<(usize, usize)>,
+}
 
 #[derive(Clone, Copy)]
 pub struct SyntaxRenderConfig<'a> {
-    pub language: SyntaxLanguage,
+    /// Reglas del lenguaje del archivo, o `None` para texto plano
+    pub language: Option<&'a LanguageDef>,
     pub syntax_theme: &'a SyntaxTheme,
+
+    /// Estado del resaltado al inicio de la línea (comentarios o strings abiertos)
+    pub line_state: LineState,
 }
 
 pub fn render_line_number<W: Write>(stdout: &mut W, line_number: usize, row: u16, width: usize) {
@@ -28,12 +43,15 @@ pub fn render_line_content<W: Write>(
     line_idx: usize,
     start_col: usize,
     search: &SearchState,
-    is_current_line: bool,
+    highlight: LineHighlight,
     syntax: SyntaxRenderConfig<'_>,
 ) {
-    let line_bg = is_current_line.then_some(Color::DarkGrey);
+    let line_bg = highlight.is_current_line.then_some(Color::DarkGrey);
     let chars: Vec<char> = line.chars().collect();
-    let tokens = tokenize_line(line, syntax.language);
+    let tokens = match syntax.language {
+        Some(language) => tokenize_line(line, language, syntax.line_state).0,
+        None => Vec::new(),
+    };
     let mut styled = String::new();
     let mut prev_style: Option<(Option<Color>, Option<Color>)> = None;
 
@@ -43,6 +61,11 @@ pub fn render_line_content<W: Write>(
             .and_then(|token| token.map(|t| color_for_token(t, syntax.syntax_theme)));
         let bg = if is_match_col(line_idx, search, col) {
             Some(Color::Yellow)
+        } else if highlight
+            .selection
+            .is_some_and(|(from, to)| col >= from && col < to)
+        {
+            Some(Color::DarkBlue)
         } else {
             line_bg
         };
@@ -75,6 +98,18 @@ fn color_for_token(token: crate::syntax::TokenKind, theme: &SyntaxTheme) -> Colo
         crate::syntax::TokenKind::String => theme.string,
         crate::syntax::TokenKind::Number => theme.number,
         crate::syntax::TokenKind::Comment => theme.comment,
+        crate::syntax::TokenKind::Type => theme.type_name,
+        crate::syntax::TokenKind::Function => theme.function,
+        crate::syntax::TokenKind::Macro => theme.macro_call,
+        crate::syntax::TokenKind::Attribute => theme.attribute,
+        crate::syntax::TokenKind::Lifetime => theme.lifetime,
+        crate::syntax::TokenKind::Char => theme.character,
+        crate::syntax::TokenKind::Operator => theme.operator,
+        crate::syntax::TokenKind::Heading => theme.heading,
+        crate::syntax::TokenKind::Key => theme.key,
+        crate::syntax::TokenKind::Variable => theme.variable,
+        crate::syntax::TokenKind::Emphasis => theme.emphasis,
+        crate::syntax::TokenKind::Tag => theme.tag,
     }
 }
 
@@ -89,26 +124,50 @@ fn is_match_col(line_idx: usize, search: &SearchState, col: usize) -> bool {
         .any(|m| m.line == line_idx && col >= m.start_col && col < m.end_col)
 }
 
-pub fn language_from_filename(filename: Option<&str>) -> SyntaxLanguage {
-    detect_language(filename)
+/// Calcula las columnas seleccionadas de una línea
+///
+/// # Argumentos
+/// * `line_idx` - Índice de la línea a evaluar
+/// * `start` - Inicio de la selección (línea, columna)
+/// * `end` - Fin de la selección (línea, columna), exclusivo
+///
+/// # Retorna
+/// El rango `(desde, hasta)` de columnas seleccionadas, o `None` si la
+/// línea queda fuera de la selección
+pub fn selection_columns(
+    line_idx: usize,
+    start: (usize, usize),
+    end: (usize, usize),
+) -> Option<(usize, usize)> {
+    if line_idx < start.0 || line_idx > end.0 {
+        return None;
+    }
+
+    let from = if line_idx == start.0 { start.1 } else { 0 };
+    let to = if line_idx == end.0 { end.1 } else { usize::MAX };
+    Some((from, to))
 }
 
-pub fn render_status_bar<W: Write>(
-    stdout: &mut W,
-    row: u16,
-    filename: Option<&str>,
-    cursor_line: usize,
-    total_lines: usize,
-    cursor_col: usize,
-) {
-    let file_info = filename.unwrap_or("[Sin nombre]");
+/// Datos que se muestran en la barra de estado
+pub struct StatusBarInfo<'a> {
+    pub filename: Option<&'a str>,
+    pub cursor_line: usize,
+    pub total_lines: usize,
+    pub cursor_col: usize,
+    pub modified: bool,
+    pub line_ending: &'a str,
+    pub encoding: &'a str,
+
+    /// Posición (1-based) del buffer activo y cantidad de buffers abiertos
+    pub buffer: (usize, usize),
+    pub read_only: bool,
+}
+
+pub fn render_status_bar<W: Write>(stdout: &mut W, row: u16, info: &StatusBarInfo<'_>) {
     let width = terminal::size()
         .map(|(width, _)| width as usize)
         .unwrap_or(0);
-    let status_text = format!(
-        "{} | Linea {}/{}, Col {}",
-        file_info, cursor_line, total_lines, cursor_col
-    );
+    let status_text = format_status(info);
     let visible_text = truncate_with_ellipsis(&status_text, width);
     let padded_text = pad_to_width(&visible_text, width);
     write!(
@@ -129,6 +188,29 @@ pub fn render_status_bar<W: Write>(
     .unwrap();
 }
 
+fn format_status(info: &StatusBarInfo<'_>) -> String {
+    let file_info = info.filename.unwrap_or("[Sin nombre]");
+    let modified_marker = if info.modified { " [+]" } else { "" };
+    let read_only_marker = if info.read_only { " [RO]" } else { "" };
+    let buffer_marker = match info.buffer {
+        (_, 0 | 1) => String::new(),
+        (active, count) => format!("[{}/{}] ", active, count),
+    };
+
+    format!(
+        "{}{}{}{} | Linea {}/{}, Col {} | {} | {}",
+        buffer_marker,
+        file_info,
+        modified_marker,
+        read_only_marker,
+        info.cursor_line,
+        info.total_lines,
+        info.cursor_col,
+        info.encoding,
+        info.line_ending
+    )
+}
+
 pub fn render_message<W: Write>(stdout: &mut W, row: u16, message: &str) {
     let width = terminal::size()
         .map(|(width, _)| width as usize)
@@ -145,6 +227,161 @@ pub fn render_message<W: Write>(stdout: &mut W, row: u16, message: &str) {
     .unwrap();
 }
 
+/// Dibuja una lista a pantalla completa, dejando libre la última fila para un prompt
+///
+/// # Argumentos
+/// * `stdout` - Terminal donde escribir
+/// * `title` - Título mostrado en la primera fila
+/// * `items` - Líneas de la lista
+/// * `selected` - Elemento resaltado; la lista se desplaza para mantenerlo visible
+pub fn render_list<W: Write>(
+    stdout: &mut W,
+    title: &str,
+    items: &[String],
+    selected: Option<usize>,
+) {
+    let (width, height) = terminal::size()
+        .map(|(width, height)| (width as usize, height))
+        .unwrap_or((80, 24));
+    let visible_rows = height.saturating_sub(2) as usize;
+    let first = list_scroll_offset(selected.unwrap_or(0), visible_rows);
+
+    write!(
+        stdout,
+        "{}{}",
+        cursor::Hide,
+        terminal::Clear(terminal::ClearType::All)
+    )
+    .unwrap();
+    write!(
+        stdout,
+        "{}{}{}",
+        cursor::MoveTo(0, 0),
+        SetBackgroundColor(Color::White),
+        SetForegroundColor(Color::Black)
+    )
+    .unwrap();
+    write!(
+        stdout,
+        "{}{}",
+        pad_to_width(&truncate_with_ellipsis(title, width), width),
+        ResetColor
+    )
+    .unwrap();
+
+    for (row, (idx, item)) in items
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(visible_rows)
+        .enumerate()
+    {
+        write!(stdout, "{}", cursor::MoveTo(0, row as u16 + 1)).unwrap();
+        let text = truncate_with_ellipsis(item, width);
+        if selected == Some(idx) {
+            write!(
+                stdout,
+                "{}{}{}",
+                SetBackgroundColor(Color::DarkBlue),
+                pad_to_width(&text, width),
+                ResetColor
+            )
+            .unwrap();
+        } else {
+            write!(stdout, "{}", text).unwrap();
+        }
+    }
+
+    stdout.flush().unwrap();
+}
+
+/// Dibuja el buscador de archivos: resultados a la izquierda y vista previa a la derecha
+///
+/// Como `render_list`, deja libre la última fila para el prompt.
+///
+/// # Argumentos
+/// * `stdout` - Terminal donde escribir
+/// * `title` - Título mostrado en la primera fila
+/// * `items` - Todos los elementos
+/// * `matches` - Índices en `items` de los resultados, en orden
+/// * `selected` - Posición del resultado resaltado dentro de `matches`
+/// * `preview` - Líneas de la vista previa del resultado resaltado
+pub fn render_finder<W: Write>(
+    stdout: &mut W,
+    title: &str,
+    items: &[String],
+    matches: &[usize],
+    selected: usize,
+    preview: &[String],
+) {
+    let (width, height) = terminal::size()
+        .map(|(width, height)| (width as usize, height))
+        .unwrap_or((80, 24));
+    let visible_rows = height.saturating_sub(2) as usize;
+    let first = list_scroll_offset(selected, visible_rows);
+    let list_width = (width / 2).max(1);
+    let preview_width = width.saturating_sub(list_width + 1);
+
+    write!(
+        stdout,
+        "{}{}{}{}{}{}",
+        cursor::Hide,
+        terminal::Clear(terminal::ClearType::All),
+        cursor::MoveTo(0, 0),
+        SetBackgroundColor(Color::White),
+        SetForegroundColor(Color::Black),
+        pad_to_width(&truncate_with_ellipsis(title, width), width),
+    )
+    .unwrap();
+    write!(stdout, "{}", ResetColor).unwrap();
+
+    for row in 0..visible_rows {
+        write!(stdout, "{}", cursor::MoveTo(0, row as u16 + 1)).unwrap();
+
+        let position = first + row;
+        let text = matches
+            .get(position)
+            .map(|&idx| {
+                // Un nombre de archivo puede contener `\r` o ESC
+                let name = replace_control_chars(&items[idx]);
+                pad_to_width(&truncate_with_ellipsis(&name, list_width), list_width)
+            })
+            .unwrap_or_else(|| " ".repeat(list_width));
+        if position == selected && position < matches.len() {
+            write!(
+                stdout,
+                "{}{}{}",
+                SetBackgroundColor(Color::DarkBlue),
+                text,
+                ResetColor
+            )
+            .unwrap();
+        } else {
+            write!(stdout, "{}", text).unwrap();
+        }
+
+        let line = preview.get(row).map(String::as_str).unwrap_or("");
+        write!(
+            stdout,
+            "{}│{}{}",
+            SetForegroundColor(Color::DarkGrey),
+            ResetColor,
+            truncate_with_ellipsis(line, preview_width)
+        )
+        .unwrap();
+    }
+
+    stdout.flush().unwrap();
+}
+
+/// Primer elemento visible de una lista para que `selected` quede en pantalla
+fn list_scroll_offset(selected: usize, visible_rows: usize) -> usize {
+    if visible_rows == 0 {
+        return selected;
+    }
+    (selected + 1).saturating_sub(visible_rows)
+}
+
 pub fn calculate_line_number_width(total_lines: usize) -> usize {
     total_lines.to_string().len() + 2
 }
@@ -167,8 +404,29 @@ pub fn position_cursor<W: Write>(stdout: &mut W, x: u16, y: u16) {
     write!(stdout, "{}", cursor::MoveTo(x, y)).unwrap();
 }
 
+/// Ancho en columnas de la terminal (los caracteres CJK ocupan dos)
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Reemplaza los caracteres de control (salvo el tabulador) por `�`
+///
+/// Escritos tal cual en el terminal, un `\r` o una secuencia de escape del
+/// archivo moverían el cursor o cambiarían colores fuera de la vista.
+pub fn replace_control_chars(text: &str) -> String {
+    text.chars()
+        .map(|ch| {
+            if ch.is_control() && ch != '\t' {
+                char::REPLACEMENT_CHARACTER
+            } else {
+                ch
+            }
+        })
+        .collect()
+}
+
 fn truncate_with_ellipsis(text: &str, max_width: usize) -> String {
-    let text_width = text.chars().count();
+    let text_width = display_width(text);
     if max_width == 0 {
         return String::new();
     }
@@ -178,12 +436,22 @@ fn truncate_with_ellipsis(text: &str, max_width: usize) -> String {
     if max_width == 1 {
         return "…".to_string();
     }
-    let truncated: String = text.chars().take(max_width - 1).collect();
+
+    let mut truncated = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = UnicodeWidthChar::width(c).unwrap_or(0);
+        if used + w > max_width - 1 {
+            break;
+        }
+        truncated.push(c);
+        used += w;
+    }
     format!("{}…", truncated)
 }
 
 fn pad_to_width(text: &str, width: usize) -> String {
-    let text_width = text.chars().count();
+    let text_width = display_width(text);
     if text_width >= width {
         return text.to_string();
     }
@@ -197,6 +465,14 @@ fn pad_to_width(text: &str, width: usize) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn control_characters_are_replaced_except_tab() {
+        assert_eq!(
+            replace_control_chars("a\tb\x1b[2J\rc\u{9b}"),
+            "a\tb\u{FFFD}[2J\u{FFFD}c\u{FFFD}"
+        );
+    }
+
     #[test]
     fn test_calculate_line_number_width() {
         assert_eq!(calculate_line_number_width(9), 3);
@@ -218,4 +494,48 @@ mod tests {
         assert_eq!(x, 14);
         assert_eq!(y, 15);
     }
+
+    #[test]
+    fn test_list_scroll_offset_keeps_selection_visible() {
+        assert_eq!(list_scroll_offset(0, 10), 0);
+        assert_eq!(list_scroll_offset(9, 10), 0);
+        assert_eq!(list_scroll_offset(10, 10), 1);
+        assert_eq!(list_scroll_offset(25, 10), 16);
+    }
+
+    #[test]
+    fn test_format_status() {
+        let info = StatusBarInfo {
+            filename: Some("main.rs"),
+            cursor_line: 3,
+            total_lines: 10,
+            cursor_col: 5,
+            modified: true,
+            line_ending: "CRLF",
+            encoding: "windows-1252",
+            buffer: (1, 1),
+            read_only: false,
+        };
+
+        assert_eq!(
+            format_status(&info),
+            "main.rs [+] | Linea 3/10, Col 5 | windows-1252 | CRLF"
+        );
+
+        let info = StatusBarInfo {
+            buffer: (2, 3),
+            read_only: true,
+            ..info
+        };
+        assert!(format_status(&info).starts_with("[2/3] main.rs [+] [RO]"));
+    }
+
+    #[test]
+    fn test_selection_columns() {
+        assert_eq!(selection_columns(0, (1, 2), (3, 4)), None);
+        assert_eq!(selection_columns(1, (1, 2), (3, 4)), Some((2, usize::MAX)));
+        assert_eq!(selection_columns(2, (1, 2), (3, 4)), Some((0, usize::MAX)));
+        assert_eq!(selection_columns(3, (1, 2), (3, 4)), Some((0, 4)));
+        assert_eq!(selection_columns(1, (1, 2), (1, 4)), Some((2, 4)));
+    }
 }
@@ -0,0 +1,319 @@
+//! Historial de deshacer/rehacer
+//!
+//! Cada modificación del buffer se registra como una edición reversible
+//! (inserción o borrado de texto en un índice de carácter del rope). Las
+//! ediciones se agrupan en pasos de deshacer que recuerdan la posición del
+//! cursor antes y después del cambio.
+
+/// Cantidad máxima de pasos que se conservan en el historial
+const MAX_UNDO_STEPS: usize = 1000;
+
+/// Tipo de edición aplicada sobre el texto
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditKind {
+    Insert,
+    Delete,
+}
+
+/// Edición atómica expresada en índices de carácter del rope
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    pub kind: EditKind,
+
+    /// Índice de carácter donde empieza la edición
+    pub char_idx: usize,
+
+    /// Texto insertado o eliminado
+    pub text: String,
+}
+
+impl Edit {
+    pub fn insert(char_idx: usize, text: &str) -> Self {
+        Edit {
+            kind: EditKind::Insert,
+            char_idx,
+            text: text.to_string(),
+        }
+    }
+
+    pub fn delete(char_idx: usize, text: &str) -> Self {
+        Edit {
+            kind: EditKind::Delete,
+            char_idx,
+            text: text.to_string(),
+        }
+    }
+
+    /// Índice de carácter inmediatamente posterior al texto de la edición
+    fn end_idx(&self) -> usize {
+        self.char_idx + self.text.chars().count()
+    }
+}
+
+/// Paso de deshacer: una o más ediciones que se revierten juntas
+#[derive(Clone, Debug)]
+pub struct UndoStep {
+    pub edits: Vec<Edit>,
+
+    /// Posición (línea, columna) del cursor antes del cambio
+    pub cursor_before: (usize, usize),
+
+    /// Posición (línea, columna) del cursor después del cambio
+    pub cursor_after: (usize, usize),
+
+    /// Indica si el paso es escritura continua que puede absorber más caracteres
+    typing: bool,
+
+    /// Identificador único, asignado al agregarlo al historial
+    id: u64,
+}
+
+/// Pilas de deshacer y rehacer
+pub struct History {
+    undo_stack: Vec<UndoStep>,
+    redo_stack: Vec<UndoStep>,
+    group: Option<UndoStep>,
+    group_depth: usize,
+
+    /// Último identificador asignado a un paso
+    last_id: u64,
+
+    /// Identificador del estado con la pila de deshacer vacía; cambia cuando
+    /// se descartan pasos viejos por `MAX_UNDO_STEPS`
+    base_id: u64,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            group: None,
+            group_depth: 0,
+            last_id: 0,
+            base_id: 0,
+        }
+    }
+
+    /// Identifica el estado actual del texto dentro del historial
+    ///
+    /// Deshacer y rehacer hasta el mismo punto da el mismo valor, así se
+    /// puede saber si el texto volvió al estado en que se guardó.
+    pub fn state_id(&self) -> u64 {
+        self.undo_stack.last().map_or(self.base_id, |step| step.id)
+    }
+
+    /// Cierra el paso actual para que la próxima escritura no se una a él
+    ///
+    /// Se usa al guardar: si no, seguir escribiendo modificaría el paso que
+    /// identifica al estado guardado.
+    pub fn seal(&mut self) {
+        if let Some(last) = self.undo_stack.last_mut() {
+            last.typing = false;
+        }
+    }
+
+    /// Registra una edición como un paso independiente
+    ///
+    /// Si hay un grupo abierto, la edición se suma al grupo.
+    pub fn record(
+        &mut self,
+        edit: Edit,
+        cursor_before: (usize, usize),
+        cursor_after: (usize, usize),
+    ) {
+        self.push(edit, cursor_before, cursor_after, false);
+    }
+
+    /// Registra un carácter escrito, uniéndolo al paso anterior si es
+    /// continuación directa de la escritura
+    pub fn record_typing(
+        &mut self,
+        edit: Edit,
+        cursor_before: (usize, usize),
+        cursor_after: (usize, usize),
+    ) {
+        let mergeable = self.group.is_none()
+            && !edit.text.contains('\n')
+            && self.undo_stack.last().is_some_and(|step| {
+                step.typing
+                    && step
+                        .edits
+                        .last()
+                        .is_some_and(|last| last.end_idx() == edit.char_idx)
+            });
+
+        if mergeable {
+            self.redo_stack.clear();
+            if let Some(step) = self.undo_stack.last_mut() {
+                if let Some(last) = step.edits.last_mut() {
+                    last.text.push_str(&edit.text);
+                }
+                step.cursor_after = cursor_after;
+            }
+            return;
+        }
+
+        let typing = !edit.text.contains('\n');
+        self.push(edit, cursor_before, cursor_after, typing);
+    }
+
+    /// Abre un grupo: todas las ediciones hasta `end_group` forman un único paso
+    pub fn begin_group(&mut self, cursor: (usize, usize)) {
+        if self.group_depth == 0 {
+            self.group = Some(UndoStep {
+                edits: Vec::new(),
+                cursor_before: cursor,
+                cursor_after: cursor,
+                typing: false,
+                id: 0,
+            });
+        }
+        self.group_depth += 1;
+    }
+
+    /// Cierra el grupo abierto y lo agrega al historial si contiene ediciones
+    pub fn end_group(&mut self, cursor: (usize, usize)) {
+        if self.group_depth == 0 {
+            return;
+        }
+
+        self.group_depth -= 1;
+        if self.group_depth > 0 {
+            return;
+        }
+
+        if let Some(mut step) = self.group.take()
+            && !step.edits.is_empty()
+        {
+            step.cursor_after = cursor;
+            self.push_step(step);
+        }
+    }
+
+    /// Extrae el último paso para deshacerlo, moviéndolo a la pila de rehacer
+    pub fn undo(&mut self) -> Option<UndoStep> {
+        let step = self.undo_stack.pop()?;
+        self.redo_stack.push(step.clone());
+        Some(step)
+    }
+
+    /// Extrae el último paso deshecho para rehacerlo
+    pub fn redo(&mut self) -> Option<UndoStep> {
+        let mut step = self.redo_stack.pop()?;
+        step.typing = false;
+        self.undo_stack.push(step.clone());
+        Some(step)
+    }
+
+    fn push(
+        &mut self,
+        edit: Edit,
+        cursor_before: (usize, usize),
+        cursor_after: (usize, usize),
+        typing: bool,
+    ) {
+        if let Some(group) = self.group.as_mut() {
+            group.edits.push(edit);
+            group.cursor_after = cursor_after;
+            return;
+        }
+
+        self.push_step(UndoStep {
+            edits: vec![edit],
+            cursor_before,
+            cursor_after,
+            typing,
+            id: 0,
+        });
+    }
+
+    fn push_step(&mut self, mut step: UndoStep) {
+        self.redo_stack.clear();
+        if let Some(last) = self.undo_stack.last_mut() {
+            last.typing = false;
+        }
+        self.last_id += 1;
+        step.id = self.last_id;
+        self.undo_stack.push(step);
+
+        if self.undo_stack.len() > MAX_UNDO_STEPS {
+            self.base_id = self.undo_stack.remove(0).id;
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typing_is_merged_into_one_step() {
+        let mut history = History::new();
+        history.record_typing(Edit::insert(0, "h"), (0, 0), (0, 1));
+        history.record_typing(Edit::insert(1, "i"), (0, 1), (0, 2));
+
+        let step = history.undo().unwrap();
+        assert_eq!(step.edits, vec![Edit::insert(0, "hi")]);
+        assert_eq!(step.cursor_before, (0, 0));
+        assert_eq!(step.cursor_after, (0, 2));
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn test_non_contiguous_typing_starts_new_step() {
+        let mut history = History::new();
+        history.record_typing(Edit::insert(0, "a"), (0, 0), (0, 1));
+        history.record_typing(Edit::insert(5, "b"), (0, 5), (0, 6));
+
+        history.undo();
+        assert!(history.undo().is_some());
+    }
+
+    #[test]
+    fn test_group_collects_edits() {
+        let mut history = History::new();
+        history.begin_group((0, 3));
+        history.record(Edit::delete(2, "c"), (0, 3), (0, 2));
+        history.record(Edit::insert(2, "x"), (0, 2), (0, 3));
+        history.end_group((0, 3));
+
+        let step = history.undo().unwrap();
+        assert_eq!(step.edits.len(), 2);
+        assert_eq!(step.cursor_before, (0, 3));
+    }
+
+    #[test]
+    fn test_state_id_returns_after_undo_redo() {
+        let mut history = History::new();
+        let empty = history.state_id();
+        history.record_typing(Edit::insert(0, "a"), (0, 0), (0, 1));
+        let saved = history.state_id();
+        history.seal();
+        history.record_typing(Edit::insert(1, "b"), (0, 1), (0, 2));
+        assert_ne!(history.state_id(), saved);
+
+        history.undo();
+        assert_eq!(history.state_id(), saved);
+        history.undo();
+        assert_eq!(history.state_id(), empty);
+        history.redo();
+        assert_eq!(history.state_id(), saved);
+    }
+
+    #[test]
+    fn test_new_edit_clears_redo() {
+        let mut history = History::new();
+        history.record(Edit::insert(0, "a"), (0, 0), (0, 1));
+        history.undo();
+        history.record(Edit::insert(0, "b"), (0, 0), (0, 1));
+
+        assert!(history.redo().is_none());
+    }
+}